@@ -0,0 +1,71 @@
+use criterion::{black_box, criterion_group, criterion_main, Criterion};
+use yogurt::argument::parser::{IntArgument, StringArgument};
+use yogurt::{Command, Dialect, Dispatcher};
+
+fn dispatcher() -> Dispatcher<(), (), ()> {
+    Dispatcher::builder()
+        .prefix("!")
+        .dialect(Dialect::extended())
+        .base_context(())
+        .context_factory(|_| ())
+        .child(Command::literal("ping").exec(|_| Ok(())))
+        .child(
+            Command::literal("give").child(
+                Command::argument("amount", IntArgument, true)
+                    .child(Command::argument("item", StringArgument, true).exec(|_| Ok(()))),
+            ),
+        )
+        .child(
+            Command::literal("ban")
+                .child(Command::argument("user", StringArgument, true).exec(|_| Ok(())))
+                .child(Command::literal("list").exec(|_| Ok(()))),
+        )
+        .build()
+        .unwrap()
+}
+
+fn dispatch(c: &mut Criterion) {
+    let dispatcher = dispatcher();
+    let mut group = c.benchmark_group("dispatch");
+    for (name, input) in [
+        ("literal", "!ping"),
+        ("arguments", "!give 64 diamond"),
+        ("named", "!give amount=64 diamond"),
+        ("quoted", r#"!ban "some user""#),
+        ("escaped", r#"!ban "some \"user\"""#),
+        ("chained", "!ping; give 1 stone; ban list"),
+    ] {
+        group.bench_function(name, |b| {
+            b.iter(|| dispatcher.run_command(black_box(input)).unwrap())
+        });
+    }
+    group.finish();
+}
+
+fn unknown(c: &mut Criterion) {
+    let dispatcher = dispatcher();
+    c.bench_function("unknown command", |b| {
+        b.iter(|| dispatcher.run_command(black_box("!give many diamonds")))
+    });
+}
+
+fn siblings(c: &mut Criterion) {
+    let mut group = c.benchmark_group("siblings");
+    for count in [10, 1000, 10000] {
+        let mut builder = Dispatcher::<(), (), ()>::builder()
+            .base_context(())
+            .context_factory(|_| ());
+        for i in 0..count {
+            builder = builder.child(Command::literal(format!("command{i}")).exec(|_| Ok(())));
+        }
+        let dispatcher = builder.build().unwrap();
+        let input = format!("command{}", count - 1);
+        group.bench_function(count.to_string(), |b| {
+            b.iter(|| dispatcher.run_command(black_box(&input)).unwrap())
+        });
+    }
+    group.finish();
+}
+
+criterion_group!(benches, dispatch, unknown, siblings);
+criterion_main!(benches);
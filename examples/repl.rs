@@ -0,0 +1,37 @@
+use std::io::Write;
+use yogurt::argument::parser::{IntArgument, StringArgument};
+use yogurt::repl::Repl;
+use yogurt::{Command, Dispatcher};
+
+fn main() {
+    let dispatcher = Dispatcher::builder()
+        .base_context(())
+        .context_factory(|_| ())
+        .child(Command::literal("ping").exec(|_| Ok("pong".to_string())))
+        .child(
+            Command::literal("echo").child(
+                Command::argument("text", StringArgument, true)
+                    .exec(|ctx| ctx.get("text", StringArgument)),
+            ),
+        )
+        .child(
+            Command::literal("add").child(Command::argument("a", IntArgument, true).child(
+                Command::argument("b", IntArgument, true).exec(|ctx| {
+                    let sum = ctx.get("a", IntArgument)? + ctx.get("b", IntArgument)?;
+                    Ok(sum.to_string())
+                }),
+            )),
+        )
+        .build()
+        .unwrap();
+
+    // exit with ctrl-d
+    let mut stdout = std::io::stdout();
+    Repl::new(&dispatcher)
+        .history_file(".yogurt_history")
+        .output(move |output| {
+            let _ = writeln!(stdout, "{output}");
+        })
+        .run()
+        .unwrap();
+}
@@ -0,0 +1,329 @@
+use crate::argument::parser::ArgumentParser;
+use crate::argument::reader::StringReader;
+use crate::parsers::escaped_string::{parse_raw_string, parse_single_quoted, parse_string};
+use crate::parsers::tokenize::Span;
+use crate::{Error, InvalidCommandReason, Result};
+use std::borrow::Cow;
+
+/// Parses a list like `a,b,c`, each element with an inner parser.
+///
+/// Elements may be quoted like tokens, `"with spaces",'or,commas',r"raw"`, and are passed to the
+/// inner parser without their quotes. Any well-formed list is accepted while resolving the command, so
+/// an invalid element is reported by [`ExecContext::get`](crate::ExecContext::get) as a
+/// [`SyntaxError`](InvalidCommandReason::SyntaxError) naming the element and located in the input.
+#[derive(Debug, Clone)]
+pub struct ListArgument<P> {
+    parser: P,
+    separator: char,
+}
+
+impl<P: ArgumentParser> ListArgument<P> {
+    /// Separates elements with `,`.
+    pub fn new(parser: P) -> Self {
+        Self {
+            parser,
+            separator: ',',
+        }
+    }
+
+    pub fn separator(mut self, separator: char) -> Self {
+        self.separator = separator;
+        self
+    }
+
+    fn read_list<'a>(&self, text: &'a str, input: bool) -> Result<(Vec<Element<'a>>, usize)> {
+        read_items(text, self.separator, |position| {
+            Element::read(text, position, &[self.separator], input)
+        })
+    }
+}
+
+impl<P: ArgumentParser> ArgumentParser for ListArgument<P> {
+    type Output = Vec<P::Output>;
+
+    fn type_name(&self) -> Cow<'static, str> {
+        "list".into()
+    }
+
+    fn parse(&self, token: &str) -> Result<Self::Output> {
+        let elements = parse_all(token, self.read_list(token, false))?;
+        elements
+            .iter()
+            .enumerate()
+            .map(|(index, element)| {
+                element.parse(&self.parser, || format!("element {}", index + 1))
+            })
+            .collect()
+    }
+
+    fn validate(&self, token: &str) -> bool {
+        parse_all(token, self.read_list(token, false)).is_ok()
+    }
+
+    /// Completes the last element.
+    fn suggest(&self, token: &str) -> Vec<String> {
+        let (done, last) = token
+            .rsplit_once(self.separator)
+            .map_or(("", token), |(done, last)| {
+                (&token[..done.len() + self.separator.len_utf8()], last)
+            });
+        self.parser
+            .suggest(last)
+            .into_iter()
+            .map(|suggestion| format!("{done}{suggestion}"))
+            .collect()
+    }
+
+    fn reads_input(&self) -> bool {
+        true
+    }
+
+    fn read(&self, reader: &mut StringReader) -> Result<()> {
+        let (_, length) = self.read_list(reader.remaining(), true)?;
+        reader.set_position(reader.position() + length)
+    }
+}
+
+/// Parses a map like `hp:10,speed:2` into its entries, in order, with parsers for the keys and
+/// the values.
+///
+/// Keys and values may be quoted and are reported like the elements of a [`ListArgument`].
+#[derive(Debug, Clone)]
+pub struct MapArgument<K, V> {
+    keys: K,
+    values: V,
+    separator: char,
+    assignment: char,
+}
+
+impl<K: ArgumentParser, V: ArgumentParser> MapArgument<K, V> {
+    /// Separates entries with `,` and keys from values with `:`.
+    pub fn new(keys: K, values: V) -> Self {
+        Self {
+            keys,
+            values,
+            separator: ',',
+            assignment: ':',
+        }
+    }
+
+    pub fn separator(mut self, separator: char) -> Self {
+        self.separator = separator;
+        self
+    }
+
+    /// Sets the character between a key and its value.
+    pub fn assignment(mut self, assignment: char) -> Self {
+        self.assignment = assignment;
+        self
+    }
+
+    fn read_map<'a>(
+        &self,
+        text: &'a str,
+        input: bool,
+    ) -> Result<(Vec<(Element<'a>, Element<'a>)>, usize)> {
+        read_items(text, self.separator, |position| {
+            let (key, end) =
+                Element::read(text, position, &[self.assignment, self.separator], input)?;
+            let Some(rest) = text[end..].strip_prefix(self.assignment) else {
+                return Err(syntax_error(
+                    format!("expected `{}` after the key", self.assignment),
+                    Span::new(end, end),
+                ));
+            };
+            let (value, end) =
+                Element::read(text, text.len() - rest.len(), &[self.separator], input)?;
+            Ok(((key, value), end))
+        })
+    }
+}
+
+impl<K: ArgumentParser, V: ArgumentParser> ArgumentParser for MapArgument<K, V> {
+    type Output = Vec<(K::Output, V::Output)>;
+
+    fn type_name(&self) -> Cow<'static, str> {
+        "map".into()
+    }
+
+    fn parse(&self, token: &str) -> Result<Self::Output> {
+        let entries = parse_all(token, self.read_map(token, false))?;
+        entries
+            .iter()
+            .enumerate()
+            .map(|(index, (key, value))| {
+                Ok((
+                    key.parse(&self.keys, || format!("key in entry {}", index + 1))?,
+                    value.parse(&self.values, || format!("value in entry {}", index + 1))?,
+                ))
+            })
+            .collect()
+    }
+
+    fn validate(&self, token: &str) -> bool {
+        parse_all(token, self.read_map(token, false)).is_ok()
+    }
+
+    /// Completes the key or the value of the last entry.
+    fn suggest(&self, token: &str) -> Vec<String> {
+        let start = token
+            .rfind(self.separator)
+            .map_or(0, |index| index + self.separator.len_utf8());
+        let (done, last) = token.split_at(start);
+        let (done, suggestions) = match last.split_once(self.assignment) {
+            Some((key, value)) => (
+                &token[..done.len() + key.len() + self.assignment.len_utf8()],
+                self.values.suggest(value),
+            ),
+            None => (done, self.keys.suggest(last)),
+        };
+        suggestions
+            .into_iter()
+            .map(|suggestion| format!("{done}{suggestion}"))
+            .collect()
+    }
+
+    fn reads_input(&self) -> bool {
+        true
+    }
+
+    fn read(&self, reader: &mut StringReader) -> Result<()> {
+        let (_, length) = self.read_map(reader.remaining(), true)?;
+        reader.set_position(reader.position() + length)
+    }
+}
+
+/// An element of a list or map, without its quotes.
+struct Element<'a> {
+    value: Cow<'a, str>,
+    span: Span,
+    /// Written without quotes, so the element's spans are also spans in the list
+    bare: bool,
+}
+
+impl<'a> Element<'a> {
+    /// Reads the element starting at `position` in `text`. Elements without quotes end before one
+    /// of `delimiters`, or whitespace and `;` when reading from the input. Returns the element and
+    /// the position after it.
+    fn read(
+        text: &'a str,
+        position: usize,
+        delimiters: &[char],
+        input: bool,
+    ) -> Result<(Self, usize)> {
+        let rest = &text[position..];
+        let quoted = match rest.chars().next() {
+            Some('"') => parse_string::<nom::error::Error<&str>>(rest)
+                .map(|(remainder, value)| (remainder, Cow::Owned(value))),
+            Some('\'') => parse_single_quoted::<nom::error::Error<&str>>(rest)
+                .map(|(remainder, value)| (remainder, Cow::Borrowed(value))),
+            Some('r') if rest[1..].trim_start_matches('#').starts_with('"') => {
+                parse_raw_string::<nom::error::Error<&str>>(rest)
+                    .map(|(remainder, value)| (remainder, Cow::Borrowed(value)))
+            }
+            _ => {
+                let length = rest
+                    .find(|c: char| {
+                        delimiters.contains(&c) || input && (c.is_whitespace() || c == ';')
+                    })
+                    .unwrap_or(rest.len());
+                if length == 0 {
+                    return Err(syntax_error(
+                        "missing element",
+                        Span::new(position, position),
+                    ));
+                }
+                let element = Element {
+                    value: Cow::Borrowed(&rest[..length]),
+                    span: Span::new(position, position + length),
+                    bare: true,
+                };
+                return Ok((element, position + length));
+            }
+        };
+
+        match quoted {
+            Ok((remainder, value)) => {
+                let end = text.len() - remainder.len();
+                let element = Element {
+                    value,
+                    span: Span::new(position, end),
+                    bare: false,
+                };
+                Ok((element, end))
+            }
+            Err(_) => Err(syntax_error(
+                "unclosed or invalid quoted string",
+                Span::new(position, text.len()),
+            )),
+        }
+    }
+
+    /// Parses the element, naming it with `describe` if it is invalid.
+    fn parse<P: ArgumentParser>(
+        &self,
+        parser: &P,
+        describe: impl Fn() -> String,
+    ) -> Result<P::Output> {
+        parser.parse(&self.value).map_err(|error| {
+            let (detail, span) = match error {
+                Error::InvalidCommand(InvalidCommandReason::SyntaxError { message, span })
+                    if self.bare =>
+                {
+                    let start = self.span.start;
+                    (
+                        Some(message),
+                        Span::new(start + span.start, start + span.end),
+                    )
+                }
+                Error::InvalidCommand(InvalidCommandReason::SyntaxError { message, .. }) => {
+                    (Some(message), self.span)
+                }
+                _ => (None, self.span),
+            };
+            let message = match detail {
+                Some(detail) => format!("invalid {}: {detail}", describe()),
+                None => format!("invalid {} `{}`", describe(), self.value),
+            };
+            syntax_error(message, span)
+        })
+    }
+}
+
+/// Reads items separated by `separator`, each read by `item` from a position to the position
+/// after it. Returns the items and the length read.
+fn read_items<T>(
+    text: &str,
+    separator: char,
+    mut item: impl FnMut(usize) -> Result<(T, usize)>,
+) -> Result<(Vec<T>, usize)> {
+    let mut items = vec![];
+    let mut position = 0;
+    loop {
+        let (value, end) = item(position)?;
+        items.push(value);
+        match text[end..].starts_with(separator) {
+            true => position = end + separator.len_utf8(),
+            false => return Ok((items, end)),
+        }
+    }
+}
+
+/// Checks that all of `text` was read.
+fn parse_all<T>(text: &str, read: Result<(T, usize)>) -> Result<T> {
+    let (items, length) = read?;
+    match text[length..].chars().next() {
+        None => Ok(items),
+        Some(c) => Err(syntax_error(
+            format!("unexpected `{c}`"),
+            Span::new(length, length + c.len_utf8()),
+        )),
+    }
+}
+
+fn syntax_error(message: impl Into<String>, span: Span) -> Error {
+    Error::InvalidCommand(InvalidCommandReason::SyntaxError {
+        message: message.into(),
+        span,
+    })
+}
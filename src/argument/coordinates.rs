@@ -0,0 +1,245 @@
+use crate::argument::parser::ArgumentParser;
+use crate::argument::reader::StringReader;
+use crate::{Error, InvalidCommandReason, Result};
+use std::borrow::Cow;
+
+/// Position and rotation that relative (`~`) and local (`^`) coordinates are resolved against,
+/// usually implemented by the command context.
+pub trait Origin {
+    /// Position as `[x, y, z]`.
+    fn position(&self) -> [f64; 3];
+
+    /// Rotation as `[yaw, pitch]` in degrees, where yaw 0 faces +z and pitch 90 faces down.
+    fn rotation(&self) -> [f64; 2] {
+        [0.0, 0.0]
+    }
+}
+
+/// A single world coordinate, `5` or `~-2`.
+#[derive(Debug, Clone, Copy, PartialEq)]
+pub enum Coordinate {
+    Absolute(f64),
+    /// Offset from the origin
+    Relative(f64),
+}
+
+impl Coordinate {
+    pub fn resolve(&self, origin: f64) -> f64 {
+        match self {
+            Coordinate::Absolute(value) => *value,
+            Coordinate::Relative(offset) => origin + offset,
+        }
+    }
+
+    fn parse(word: &str) -> Option<Self> {
+        match word.strip_prefix('~') {
+            Some(offset) => parse_offset(offset).map(Coordinate::Relative),
+            None => parse_number(word).map(Coordinate::Absolute),
+        }
+    }
+}
+
+/// A position, either in world coordinates, `1 ~ ~-2`, or local to the origin's rotation,
+/// `^left ^up ^forward`.
+#[derive(Debug, Clone, Copy, PartialEq)]
+pub enum Vec3 {
+    World([Coordinate; 3]),
+    Local([f64; 3]),
+}
+
+impl Vec3 {
+    pub fn resolve(&self, origin: &impl Origin) -> [f64; 3] {
+        let position = origin.position();
+        match self {
+            Vec3::World(coordinates) => {
+                std::array::from_fn(|axis| coordinates[axis].resolve(position[axis]))
+            }
+            Vec3::Local([left, up, forward]) => {
+                let [yaw, pitch] = origin.rotation();
+                let (yaw, pitch) = ((yaw + 90.0).to_radians(), (-pitch).to_radians());
+                let forward_axis = [
+                    yaw.cos() * pitch.cos(),
+                    pitch.sin(),
+                    yaw.sin() * pitch.cos(),
+                ];
+                let up_axis = [
+                    -yaw.cos() * pitch.sin(),
+                    pitch.cos(),
+                    -yaw.sin() * pitch.sin(),
+                ];
+                let left_axis = [
+                    forward_axis[2] * up_axis[1] - forward_axis[1] * up_axis[2],
+                    forward_axis[0] * up_axis[2] - forward_axis[2] * up_axis[0],
+                    forward_axis[1] * up_axis[0] - forward_axis[0] * up_axis[1],
+                ];
+                std::array::from_fn(|axis| {
+                    position[axis]
+                        + left_axis[axis] * left
+                        + up_axis[axis] * up
+                        + forward_axis[axis] * forward
+                })
+            }
+        }
+    }
+}
+
+/// A horizontal position, `x z`.
+#[derive(Debug, Clone, Copy, PartialEq)]
+pub struct Vec2(pub [Coordinate; 2]);
+
+impl Vec2 {
+    /// Resolves against the x and z coordinates of the origin.
+    pub fn resolve(&self, origin: &impl Origin) -> [f64; 2] {
+        let [x, _, z] = origin.position();
+        [self.0[0].resolve(x), self.0[1].resolve(z)]
+    }
+}
+
+/// A rotation, `yaw pitch` in degrees.
+#[derive(Debug, Clone, Copy, PartialEq)]
+pub struct Rotation(pub [Coordinate; 2]);
+
+impl Rotation {
+    pub fn resolve(&self, origin: &impl Origin) -> [f64; 2] {
+        let [yaw, pitch] = origin.rotation();
+        [self.0[0].resolve(yaw), self.0[1].resolve(pitch)]
+    }
+}
+
+/// Parses a [`Vec3`] from three tokens.
+#[derive(Debug, Clone, Default)]
+pub struct Vec3Argument;
+
+impl ArgumentParser for Vec3Argument {
+    type Output = Vec3;
+
+    fn type_name(&self) -> Cow<'static, str> {
+        "vec3".into()
+    }
+
+    fn parse(&self, token: &str) -> Result<Self::Output> {
+        let words: [&str; 3] = split(token)?;
+        let local = words.map(|word| word.strip_prefix('^'));
+        if local.iter().all(Option::is_none) {
+            return coordinates(words).map(Vec3::World);
+        }
+
+        // local coordinates cannot be mixed with world coordinates
+        let mut offsets = [0.0; 3];
+        for (offset, word) in offsets.iter_mut().zip(local) {
+            *offset = word
+                .and_then(parse_offset)
+                .ok_or(Error::InvalidCommand(InvalidCommandReason::InvalidArgument))?;
+        }
+        Ok(Vec3::Local(offsets))
+    }
+
+    fn usage(&self) -> Option<String> {
+        Some("x y z".to_string())
+    }
+
+    fn reads_input(&self) -> bool {
+        true
+    }
+
+    fn read(&self, reader: &mut StringReader) -> Result<()> {
+        read_words(reader, 3)
+    }
+}
+
+/// Parses a [`Vec2`] from two tokens.
+#[derive(Debug, Clone, Default)]
+pub struct Vec2Argument;
+
+impl ArgumentParser for Vec2Argument {
+    type Output = Vec2;
+
+    fn type_name(&self) -> Cow<'static, str> {
+        "vec2".into()
+    }
+
+    fn parse(&self, token: &str) -> Result<Self::Output> {
+        coordinates(split(token)?).map(Vec2)
+    }
+
+    fn usage(&self) -> Option<String> {
+        Some("x z".to_string())
+    }
+
+    fn reads_input(&self) -> bool {
+        true
+    }
+
+    fn read(&self, reader: &mut StringReader) -> Result<()> {
+        read_words(reader, 2)
+    }
+}
+
+/// Parses a [`Rotation`] from two tokens.
+#[derive(Debug, Clone, Default)]
+pub struct RotationArgument;
+
+impl ArgumentParser for RotationArgument {
+    type Output = Rotation;
+
+    fn type_name(&self) -> Cow<'static, str> {
+        "rotation".into()
+    }
+
+    fn parse(&self, token: &str) -> Result<Self::Output> {
+        coordinates(split(token)?).map(Rotation)
+    }
+
+    fn usage(&self) -> Option<String> {
+        Some("yaw pitch".to_string())
+    }
+
+    fn reads_input(&self) -> bool {
+        true
+    }
+
+    fn read(&self, reader: &mut StringReader) -> Result<()> {
+        read_words(reader, 2)
+    }
+}
+
+fn read_words(reader: &mut StringReader, count: usize) -> Result<()> {
+    for index in 0..count {
+        if index > 0 && reader.skip_whitespace().is_empty() {
+            return Err(Error::InvalidCommand(InvalidCommandReason::InvalidArgument));
+        }
+        reader.read_word()?;
+    }
+    Ok(())
+}
+
+/// Splits a value into exactly `N` words.
+fn split<const N: usize>(value: &str) -> Result<[&str; N]> {
+    let mut words = value.split_whitespace();
+    let split = std::array::from_fn(|_| words.next().unwrap_or_default());
+    match words.next().is_none() && split.iter().all(|word| !word.is_empty()) {
+        true => Ok(split),
+        false => Err(Error::InvalidCommand(InvalidCommandReason::InvalidArgument)),
+    }
+}
+
+fn coordinates<const N: usize>(words: [&str; N]) -> Result<[Coordinate; N]> {
+    let mut coordinates = [Coordinate::Absolute(0.0); N];
+    for (coordinate, word) in coordinates.iter_mut().zip(words) {
+        *coordinate = Coordinate::parse(word)
+            .ok_or(Error::InvalidCommand(InvalidCommandReason::InvalidArgument))?;
+    }
+    Ok(coordinates)
+}
+
+fn parse_number(value: &str) -> Option<f64> {
+    value.parse().ok().filter(|number: &f64| number.is_finite())
+}
+
+/// The number after `~` or `^`, which defaults to zero.
+fn parse_offset(value: &str) -> Option<f64> {
+    match value.is_empty() {
+        true => Some(0.0),
+        false => parse_number(value),
+    }
+}
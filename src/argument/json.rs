@@ -0,0 +1,93 @@
+use crate::argument::parser::ArgumentParser;
+use crate::argument::reader::StringReader;
+use crate::parsers::tokenize::Span;
+use crate::{Error, InvalidCommandReason, Result};
+use serde::de::DeserializeOwned;
+use serde_json::Value;
+use std::borrow::Cow;
+use std::fmt::{Debug, Formatter};
+use std::marker::PhantomData;
+
+/// Parses an inline JSON value into `T`, a [`Value`] by default.
+///
+/// Objects and arrays are read from the input up to their closing bracket, spaces and `;`
+/// included. Any balanced value is accepted while resolving the command, so malformed JSON is
+/// reported by [`ExecContext::get`](crate::ExecContext::get) as a
+/// [`SyntaxError`](InvalidCommandReason::SyntaxError) located in the input.
+pub struct JsonArgument<T = Value>(PhantomData<fn() -> T>);
+
+impl<T> JsonArgument<T> {
+    pub fn new() -> Self {
+        Self(PhantomData)
+    }
+}
+
+impl<T> Default for JsonArgument<T> {
+    fn default() -> Self {
+        Self::new()
+    }
+}
+
+impl<T> Clone for JsonArgument<T> {
+    fn clone(&self) -> Self {
+        Self::new()
+    }
+}
+
+impl<T> Debug for JsonArgument<T> {
+    fn fmt(&self, f: &mut Formatter<'_>) -> std::fmt::Result {
+        write!(f, "JsonArgument<{}>", std::any::type_name::<T>())
+    }
+}
+
+impl<T: DeserializeOwned> ArgumentParser for JsonArgument<T> {
+    type Output = T;
+
+    fn type_name(&self) -> Cow<'static, str> {
+        "json".into()
+    }
+
+    fn parse(&self, token: &str) -> Result<Self::Output> {
+        serde_json::from_str(token).map_err(|error| syntax_error(token, &error))
+    }
+
+    fn validate(&self, _token: &str) -> bool {
+        true
+    }
+
+    fn reads_input(&self) -> bool {
+        true
+    }
+
+    fn read(&self, reader: &mut StringReader) -> Result<()> {
+        match reader.peek() {
+            Some('{' | '[') => reader.read_bracketed(&['"']).map(|_| ()),
+            Some('"') => reader.read_quoted().map(|_| ()),
+            _ => reader.read_word().map(|_| ()),
+        }
+    }
+}
+
+/// Converts the line and column of `error` to a span in `json`.
+fn syntax_error(json: &str, error: &serde_json::Error) -> Error {
+    let line_start: usize = json
+        .split_inclusive('\n')
+        .take(error.line().saturating_sub(1))
+        .map(str::len)
+        .sum();
+    let start = (line_start + error.column().saturating_sub(1)).min(json.len());
+    let end = json[start..]
+        .chars()
+        .next()
+        .map_or(start, |c| start + c.len_utf8());
+
+    let message = error.to_string();
+    let position = format!(" at line {} column {}", error.line(), error.column());
+    Error::InvalidCommand(InvalidCommandReason::SyntaxError {
+        message: message
+            .strip_suffix(&position)
+            .unwrap_or(&message)
+            .to_string(),
+        span: Span::new(start, end),
+    })
+}
@@ -1,22 +1,146 @@
+use crate::Result;
+use parser::ArgumentParser;
+use reader::StringReader;
+use std::borrow::Cow;
+
+pub mod collection;
+pub mod coordinates;
+#[cfg(feature = "json")]
+pub mod json;
 pub mod parser;
+pub mod path;
+pub mod reader;
+pub mod resource;
+pub mod snbt;
+
+/// Type-erased view of an [`ArgumentParser`], as stored in the command tree.
+trait Validator: Send + Sync {
+    fn validate(&self, token: &str) -> bool;
+
+    fn suggest(&self, token: &str) -> Vec<String>;
+
+    fn usage(&self) -> Option<String>;
+
+    fn reads_input(&self) -> bool;
+
+    fn read(&self, reader: &mut StringReader) -> Result<()>;
+}
+
+impl<P: ArgumentParser + Send + Sync> Validator for P {
+    fn validate(&self, token: &str) -> bool {
+        ArgumentParser::validate(self, token)
+    }
+
+    fn suggest(&self, token: &str) -> Vec<String> {
+        ArgumentParser::suggest(self, token)
+    }
+
+    fn usage(&self) -> Option<String> {
+        ArgumentParser::usage(self)
+    }
+
+    fn reads_input(&self) -> bool {
+        ArgumentParser::reads_input(self)
+    }
+
+    fn read(&self, reader: &mut StringReader) -> Result<()> {
+        ArgumentParser::read(self, reader)
+    }
+}
+
+/// Validator given as a plain function.
+struct ValidatorFn(fn(&str) -> bool);
+
+impl Validator for ValidatorFn {
+    fn validate(&self, token: &str) -> bool {
+        (self.0)(token)
+    }
+
+    fn suggest(&self, _token: &str) -> Vec<String> {
+        vec![]
+    }
+
+    fn usage(&self) -> Option<String> {
+        None
+    }
+
+    fn reads_input(&self) -> bool {
+        false
+    }
+
+    fn read(&self, reader: &mut StringReader) -> Result<()> {
+        reader.read_word().map(|_| ())
+    }
+}
 
 pub struct Argument {
-    validator: fn(&str) -> bool,
+    validator: Box<dyn Validator>,
     pub name: String,
     required: bool,
+    type_name: Option<Cow<'static, str>>,
 }
 
 impl Argument {
     pub fn new(validator: fn(&str) -> bool, name: String, required: bool) -> Self {
         Self {
-            validator,
+            validator: Box::new(ValidatorFn(validator)),
+            name,
+            required,
+            type_name: None,
+        }
+    }
+
+    /// Creates an argument validated by `parser`.
+    pub fn with_parser(
+        parser: impl ArgumentParser + Send + Sync + 'static,
+        name: String,
+        required: bool,
+    ) -> Self {
+        Self {
+            type_name: Some(parser.type_name()),
+            validator: Box::new(parser),
             name,
             required,
         }
     }
 
+    /// Sets the name of the type the argument parses to.
+    pub fn with_type_name(mut self, type_name: impl Into<Cow<'static, str>>) -> Self {
+        self.type_name = Some(type_name.into());
+        self
+    }
+
+    pub fn type_name(&self) -> Option<&str> {
+        self.type_name.as_deref()
+    }
+
     pub fn matches(&self, sample: &str) -> bool {
-        (self.validator)(sample)
+        self.validator.validate(sample)
+    }
+
+    /// Completions for a partially typed value of the argument.
+    pub fn suggest(&self, partial: &str) -> Vec<String> {
+        self.validator.suggest(partial)
+    }
+
+    /// The argument as shown in usage, `<name>` if required and `[name]` otherwise.
+    pub fn usage(&self) -> String {
+        let placeholder = self.validator.usage();
+        let placeholder = placeholder.as_deref().unwrap_or(&self.name);
+        match self.required {
+            true => format!("<{placeholder}>"),
+            false => format!("[{placeholder}]"),
+        }
+    }
+
+    /// Whether the argument is read from the raw input instead of being a single token.
+    pub fn reads_input(&self) -> bool {
+        self.validator.reads_input()
+    }
+
+    /// Advances `reader` past the argument, see [`ArgumentParser::read`].
+    pub fn read(&self, reader: &mut StringReader) -> Result<()> {
+        self.validator.read(reader)
     }
 
     pub fn is_required(&self) -> bool {
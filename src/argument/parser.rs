@@ -1,12 +1,110 @@
+use crate::argument::reader::StringReader;
+use crate::parsers::tokenize::Span;
 use crate::{Error, InvalidCommandReason, Result};
-use std::fmt::Debug;
+use std::borrow::Cow;
+use std::fmt::{Debug, Display, Formatter};
+use std::marker::PhantomData;
+use std::net::{IpAddr, Ipv4Addr, Ipv6Addr, SocketAddr};
+use std::str::FromStr;
+use std::time::Duration;
 
 pub trait ArgumentParser: Debug + Clone {
     type Output;
 
     fn parse(&self, token: &str) -> Result<Self::Output>;
 
-    fn validator(&self) -> fn(&str) -> bool;
+    /// Whether `token` is accepted by this parser, used to match the argument while resolving a
+    /// command.
+    fn validate(&self, token: &str) -> bool {
+        self.parse(token).is_ok()
+    }
+
+    /// Completions for `token`, a partially typed argument.
+    fn suggest(&self, _token: &str) -> Vec<String> {
+        vec![]
+    }
+
+    /// Placeholder shown in usage instead of the argument name, like `a|b|c`.
+    fn usage(&self) -> Option<String> {
+        None
+    }
+
+    /// Whether the argument is read from the raw input with [`read`](Self::read), so it can span
+    /// several tokens. Otherwise, and by default, it is a single token.
+    fn reads_input(&self) -> bool {
+        false
+    }
+
+    /// Advances `reader`, positioned at the start of the argument, to its end. The text read is
+    /// then passed to [`validate`](Self::validate) and [`parse`](Self::parse). Only called if
+    /// [`reads_input`](Self::reads_input) is true.
+    ///
+    /// The argument may end inside a token written without quotes, like the number in `50%`, in
+    /// which case the rest of the token is matched by the next node.
+    ///
+    /// ```
+    /// use yogurt::argument::parser::ArgumentParser;
+    /// use yogurt::argument::reader::StringReader;
+    /// use yogurt::{Error, InvalidCommandReason, Result};
+    ///
+    /// /// A `key: value` pair, with or without spaces around the colon.
+    /// #[derive(Debug, Clone)]
+    /// struct PairArgument;
+    ///
+    /// impl ArgumentParser for PairArgument {
+    ///     type Output = (String, String);
+    ///
+    ///     fn parse(&self, token: &str) -> Result<Self::Output> {
+    ///         match token.split_once(':') {
+    ///             Some((key, value)) if !key.trim().is_empty() && !value.trim().is_empty() => {
+    ///                 Ok((key.trim().to_string(), value.trim().to_string()))
+    ///             }
+    ///             _ => Err(Error::InvalidCommand(InvalidCommandReason::InvalidArgument)),
+    ///         }
+    ///     }
+    ///
+    ///     fn reads_input(&self) -> bool {
+    ///         true
+    ///     }
+    ///
+    ///     fn read(&self, reader: &mut StringReader) -> Result<()> {
+    ///         reader.read_while(|c| !c.is_whitespace() && c != ':');
+    ///         reader.skip_whitespace();
+    ///         reader.expect(':')?;
+    ///         reader.skip_whitespace();
+    ///         reader.read_word().map(|_| ())
+    ///     }
+    /// }
+    /// ```
+    fn read(&self, reader: &mut StringReader) -> Result<()> {
+        reader.read_word().map(|_| ())
+    }
+
+    /// Name of the parsed type, used to describe arguments, e.g. when highlighting. Defaults to
+    /// the type name without module paths, like `Vec<PathBuf>`.
+    fn type_name(&self) -> Cow<'static, str> {
+        short_type_name(std::any::type_name::<Self::Output>())
+    }
+}
+
+/// Strips the module paths from every path in a type name.
+fn short_type_name(name: &'static str) -> Cow<'static, str> {
+    let is_path = |c: char| c.is_alphanumeric() || c == '_' || c == ':';
+    if name.chars().all(is_path) {
+        return Cow::Borrowed(name.rsplit("::").next().unwrap_or(name));
+    }
+
+    let mut short = String::with_capacity(name.len());
+    let mut rest = name;
+    while !rest.is_empty() {
+        let length = rest.find(|c| !is_path(c)).unwrap_or(rest.len());
+        let (path, other) = rest.split_at(length);
+        short.push_str(path.rsplit("::").next().unwrap_or(path));
+        let length = other.find(is_path).unwrap_or(other.len());
+        short.push_str(&other[..length]);
+        rest = &other[length..];
+    }
+    Cow::Owned(short)
 }
 
 #[derive(Debug, Clone)]
@@ -19,8 +117,8 @@ impl ArgumentParser for StringArgument {
         Ok(token.to_string())
     }
 
-    fn validator(&self) -> fn(&str) -> bool {
-        |_| true
+    fn validate(&self, _token: &str) -> bool {
+        true
     }
 }
 
@@ -35,10 +133,6 @@ impl ArgumentParser for IntArgument {
             .parse()
             .map_err(|_| Error::InvalidCommand(InvalidCommandReason::InvalidArgument))
     }
-
-    fn validator(&self) -> fn(&str) -> bool {
-        |str| str.parse::<i32>().is_ok()
-    }
 }
 
 #[derive(Debug, Clone)]
@@ -47,6 +141,13 @@ pub struct BoundedIntArgument {
     max: i32,
 }
 
+impl BoundedIntArgument {
+    /// Accepts integers from `min` to `max`, inclusive.
+    pub fn new(min: i32, max: i32) -> Self {
+        Self { min, max }
+    }
+}
+
 impl ArgumentParser for BoundedIntArgument {
     type Output = i32;
 
@@ -60,28 +161,747 @@ impl ArgumentParser for BoundedIntArgument {
             Err(Error::InvalidCommand(InvalidCommandReason::InvalidArgument))
         }
     }
-
-    fn validator(&self) -> fn(&str) -> bool {
-        |str| str.parse::<i32>().is_ok()
-    }
 }
 
+/// Accepts one of a fixed set of words.
 #[derive(Debug, Clone)]
 pub struct ChoiceArgument(Vec<String>);
 
+impl ChoiceArgument {
+    pub fn new<S: Into<String>>(choices: impl IntoIterator<Item = S>) -> Self {
+        Self(choices.into_iter().map(Into::into).collect())
+    }
+}
+
 impl ArgumentParser for ChoiceArgument {
     type Output = String;
 
+    fn type_name(&self) -> Cow<'static, str> {
+        "choice".into()
+    }
+
+    fn parse(&self, token: &str) -> Result<Self::Output> {
+        if self.validate(token) {
+            Ok(token.to_string())
+        } else {
+            Err(Error::InvalidCommand(InvalidCommandReason::InvalidArgument))
+        }
+    }
+
+    fn validate(&self, token: &str) -> bool {
+        self.0.iter().any(|choice| choice == token)
+    }
+
+    fn suggest(&self, token: &str) -> Vec<String> {
+        self.0
+            .iter()
+            .filter(|choice| choice.starts_with(token))
+            .cloned()
+            .collect()
+    }
+
+    fn usage(&self) -> Option<String> {
+        Some(self.0.join("|"))
+    }
+}
+
+/// Parses any type implementing [`FromStr`].
+pub struct FromStrArgument<T>(PhantomData<fn() -> T>);
+
+impl<T> FromStrArgument<T> {
+    pub fn new() -> Self {
+        Self(PhantomData)
+    }
+}
+
+impl<T> Default for FromStrArgument<T> {
+    fn default() -> Self {
+        Self::new()
+    }
+}
+
+impl<T> Clone for FromStrArgument<T> {
+    fn clone(&self) -> Self {
+        Self::new()
+    }
+}
+
+impl<T> Debug for FromStrArgument<T> {
+    fn fmt(&self, f: &mut Formatter<'_>) -> std::fmt::Result {
+        write!(f, "FromStrArgument<{}>", std::any::type_name::<T>())
+    }
+}
+
+impl<T: FromStr> ArgumentParser for FromStrArgument<T> {
+    type Output = T;
+
+    fn parse(&self, token: &str) -> Result<Self::Output> {
+        token
+            .parse()
+            .map_err(|_| Error::InvalidCommand(InvalidCommandReason::InvalidArgument))
+    }
+}
+
+/// Parses a number, optionally within inclusive bounds. Floats accept `inf` and `NaN` unless
+/// bounded.
+#[derive(Debug, Clone)]
+pub struct NumberArgument<T> {
+    min: Option<T>,
+    max: Option<T>,
+}
+
+impl<T> NumberArgument<T> {
+    pub fn new() -> Self {
+        Self {
+            min: None,
+            max: None,
+        }
+    }
+
+    pub fn min(mut self, min: T) -> Self {
+        self.min = Some(min);
+        self
+    }
+
+    pub fn max(mut self, max: T) -> Self {
+        self.max = Some(max);
+        self
+    }
+}
+
+impl<T> Default for NumberArgument<T> {
+    fn default() -> Self {
+        Self::new()
+    }
+}
+
+impl<T: FromStr + PartialOrd + Debug + Clone> ArgumentParser for NumberArgument<T> {
+    type Output = T;
+
     fn parse(&self, token: &str) -> Result<Self::Output> {
-        let token = token.to_string();
-        if self.0.contains(&token) {
-            Ok(token)
+        let number: T = FromStrArgument::new().parse(token)?;
+        let above_min = self.min.as_ref().is_none_or(|min| number >= *min);
+        let below_max = self.max.as_ref().is_none_or(|max| number <= *max);
+        if above_min && below_max {
+            Ok(number)
         } else {
             Err(Error::InvalidCommand(InvalidCommandReason::InvalidArgument))
         }
     }
+}
+
+pub type I8Argument = NumberArgument<i8>;
+pub type I16Argument = NumberArgument<i16>;
+pub type I32Argument = NumberArgument<i32>;
+pub type I64Argument = NumberArgument<i64>;
+pub type I128Argument = NumberArgument<i128>;
+pub type IsizeArgument = NumberArgument<isize>;
+pub type U8Argument = NumberArgument<u8>;
+pub type U16Argument = NumberArgument<u16>;
+pub type U32Argument = NumberArgument<u32>;
+pub type U64Argument = NumberArgument<u64>;
+pub type U128Argument = NumberArgument<u128>;
+pub type UsizeArgument = NumberArgument<usize>;
+pub type F32Argument = NumberArgument<f32>;
+pub type F64Argument = NumberArgument<f64>;
+
+/// Parses `true`/`false`, `yes`/`no` and `on`/`off`, ignoring ASCII case.
+#[derive(Debug, Clone)]
+pub struct BoolArgument;
+
+impl BoolArgument {
+    const TRUE: [&'static str; 3] = ["true", "yes", "on"];
+    const FALSE: [&'static str; 3] = ["false", "no", "off"];
+
+    fn parse_bool(token: &str) -> Option<bool> {
+        if Self::TRUE
+            .iter()
+            .any(|word| word.eq_ignore_ascii_case(token))
+        {
+            Some(true)
+        } else if Self::FALSE
+            .iter()
+            .any(|word| word.eq_ignore_ascii_case(token))
+        {
+            Some(false)
+        } else {
+            None
+        }
+    }
+}
+
+impl ArgumentParser for BoolArgument {
+    type Output = bool;
+
+    fn parse(&self, token: &str) -> Result<Self::Output> {
+        Self::parse_bool(token).ok_or(Error::InvalidCommand(InvalidCommandReason::InvalidArgument))
+    }
+
+    fn suggest(&self, token: &str) -> Vec<String> {
+        let token = token.to_ascii_lowercase();
+        Self::TRUE
+            .iter()
+            .chain(&Self::FALSE)
+            .filter(|word| word.starts_with(&token))
+            .map(|word| word.to_string())
+            .collect()
+    }
+}
+
+/// Parses durations made of numbers with unit suffixes, like `90s`, `2d` or `1h30m`.
+#[derive(Debug, Clone)]
+pub struct DurationArgument {
+    units: Vec<(String, Duration)>,
+    min: Option<Duration>,
+    max: Option<Duration>,
+}
+
+impl DurationArgument {
+    /// Accepts the units `ms`, `s`, `m`, `h`, `d` and `w`.
+    pub fn new() -> Self {
+        Self {
+            units: vec![],
+            min: None,
+            max: None,
+        }
+        .units([
+            ("ms", Duration::from_millis(1)),
+            ("s", Duration::from_secs(1)),
+            ("m", Duration::from_secs(60)),
+            ("h", Duration::from_secs(60 * 60)),
+            ("d", Duration::from_secs(24 * 60 * 60)),
+            ("w", Duration::from_secs(7 * 24 * 60 * 60)),
+        ])
+    }
+
+    /// Replaces the accepted unit suffixes and the duration of each.
+    pub fn units<S: Into<String>>(
+        mut self,
+        units: impl IntoIterator<Item = (S, Duration)>,
+    ) -> Self {
+        self.units = units
+            .into_iter()
+            .map(|(suffix, unit)| (suffix.into(), unit))
+            .collect();
+        // longest first, so `ms` is not read as minutes
+        self.units
+            .sort_by_key(|(suffix, _)| std::cmp::Reverse(suffix.len()));
+        self
+    }
+
+    pub fn min(mut self, min: Duration) -> Self {
+        self.min = Some(min);
+        self
+    }
+
+    pub fn max(mut self, max: Duration) -> Self {
+        self.max = Some(max);
+        self
+    }
+
+    fn parse_duration(&self, token: &str) -> Option<Duration> {
+        let mut rest = token;
+        let mut total = Duration::ZERO;
+        while !rest.is_empty() {
+            let digits = rest
+                .find(|c: char| !c.is_ascii_digit())
+                .unwrap_or(rest.len());
+            let count: u32 = rest[..digits].parse().ok()?;
+            rest = &rest[digits..];
+            let (suffix, unit) = self
+                .units
+                .iter()
+                .find(|(suffix, _)| rest.starts_with(suffix.as_str()))?;
+            rest = &rest[suffix.len()..];
+            total = total.checked_add(unit.checked_mul(count)?)?;
+        }
+        (!token.is_empty()).then_some(total)
+    }
+}
+
+impl Default for DurationArgument {
+    fn default() -> Self {
+        Self::new()
+    }
+}
+
+impl ArgumentParser for DurationArgument {
+    type Output = Duration;
+
+    fn type_name(&self) -> Cow<'static, str> {
+        "duration".into()
+    }
+
+    fn parse(&self, token: &str) -> Result<Self::Output> {
+        let duration = self
+            .parse_duration(token)
+            .ok_or(Error::InvalidCommand(InvalidCommandReason::InvalidArgument))?;
+        let above_min = self.min.is_none_or(|min| duration >= min);
+        let below_max = self.max.is_none_or(|max| duration <= max);
+        if above_min && below_max {
+            Ok(duration)
+        } else {
+            Err(Error::InvalidCommand(InvalidCommandReason::InvalidArgument))
+        }
+    }
+
+    /// Suggests the units after a number, `10` completes to `10s`, `10m`, ...
+    fn suggest(&self, token: &str) -> Vec<String> {
+        if !token.ends_with(|c: char| c.is_ascii_digit()) {
+            return vec![];
+        }
+        self.units
+            .iter()
+            .map(|(suffix, _)| format!("{token}{suffix}"))
+            .filter(|candidate| self.parse(candidate).is_ok())
+            .collect()
+    }
+}
+
+/// An enum that can be parsed by [`EnumArgument`].
+///
+/// ```
+/// use yogurt::argument::parser::ArgumentEnum;
+///
+/// #[derive(Debug, Clone, PartialEq)]
+/// enum GameMode {
+///     Survival,
+///     Creative,
+/// }
+///
+/// impl ArgumentEnum for GameMode {
+///     const VARIANTS: &'static [(Self, &'static [&'static str])] = &[
+///         (GameMode::Survival, &["survival", "s"]),
+///         (GameMode::Creative, &["creative", "c"]),
+///     ];
+/// }
+/// ```
+pub trait ArgumentEnum: Clone + 'static {
+    /// Each variant with its spellings. The first spelling is the name shown in completions and
+    /// usage, the others are aliases.
+    const VARIANTS: &'static [(Self, &'static [&'static str])];
+}
+
+/// Parses one of the spellings of an [`ArgumentEnum`] into the variant.
+pub struct EnumArgument<T>(PhantomData<fn() -> T>);
+
+impl<T> EnumArgument<T> {
+    pub fn new() -> Self {
+        Self(PhantomData)
+    }
+}
+
+impl<T> Default for EnumArgument<T> {
+    fn default() -> Self {
+        Self::new()
+    }
+}
+
+impl<T> Clone for EnumArgument<T> {
+    fn clone(&self) -> Self {
+        Self::new()
+    }
+}
+
+impl<T> Debug for EnumArgument<T> {
+    fn fmt(&self, f: &mut Formatter<'_>) -> std::fmt::Result {
+        write!(f, "EnumArgument<{}>", std::any::type_name::<T>())
+    }
+}
+
+impl<T: ArgumentEnum> EnumArgument<T> {
+    fn names() -> impl Iterator<Item = &'static str> {
+        T::VARIANTS
+            .iter()
+            .filter_map(|(_, spellings)| spellings.first().copied())
+    }
+}
+
+impl<T: ArgumentEnum> ArgumentParser for EnumArgument<T> {
+    type Output = T;
+
+    fn parse(&self, token: &str) -> Result<Self::Output> {
+        T::VARIANTS
+            .iter()
+            .find(|(_, spellings)| spellings.contains(&token))
+            .map(|(variant, _)| variant.clone())
+            .ok_or(Error::InvalidCommand(InvalidCommandReason::InvalidArgument))
+    }
+
+    fn suggest(&self, token: &str) -> Vec<String> {
+        Self::names()
+            .filter(|name| name.starts_with(token))
+            .map(str::to_string)
+            .collect()
+    }
+
+    fn usage(&self) -> Option<String> {
+        Some(Self::names().collect::<Vec<_>>().join("|"))
+    }
+}
+
+/// Result of parsing a network argument or a part of it, failing with a description of the
+/// problem.
+type Malformed<T> = std::result::Result<T, String>;
+
+fn malformed(message: impl Into<String>) -> String {
+    message.into()
+}
+
+/// Reports a malformed network argument as a syntax error covering `token`.
+fn located<T>(token: &str, result: Malformed<T>) -> Result<T> {
+    result.map_err(|message| {
+        Error::InvalidCommand(InvalidCommandReason::SyntaxError {
+            message,
+            span: Span::new(0, token.len()),
+        })
+    })
+}
+
+fn parse_ip(address: &str) -> Malformed<IpAddr> {
+    if address.is_empty() {
+        return Err(malformed("missing IP address"));
+    }
+    match address.contains(':') {
+        true => parse_ipv6(address).map(IpAddr::V6),
+        false => address
+            .parse::<Ipv4Addr>()
+            .map(IpAddr::V4)
+            .map_err(|_| malformed(format!("`{address}` is not a valid IPv4 address"))),
+    }
+}
+
+fn parse_ipv6(address: &str) -> Malformed<Ipv6Addr> {
+    address
+        .parse()
+        .map_err(|_| malformed(format!("`{address}` is not a valid IPv6 address")))
+}
+
+fn parse_port(port: &str) -> Malformed<u16> {
+    if port.is_empty() {
+        return Err(malformed("missing port"));
+    }
+    port.parse()
+        .map_err(|_| malformed(format!("`{port}` is not a valid port, expected 0 to 65535")))
+}
+
+/// Parses an IPv4 or IPv6 address.
+#[derive(Debug, Clone)]
+pub struct IpAddrArgument;
+
+impl ArgumentParser for IpAddrArgument {
+    type Output = IpAddr;
+
+    fn type_name(&self) -> Cow<'static, str> {
+        "ip".into()
+    }
+
+    fn parse(&self, token: &str) -> Result<Self::Output> {
+        located(token, parse_ip(token))
+    }
+}
+
+/// Parses an address with a port, `127.0.0.1:8080` or `[::1]:8080`.
+#[derive(Debug, Clone)]
+pub struct SocketAddrArgument;
+
+impl ArgumentParser for SocketAddrArgument {
+    type Output = SocketAddr;
+
+    fn type_name(&self) -> Cow<'static, str> {
+        "socket address".into()
+    }
+
+    fn parse(&self, token: &str) -> Result<Self::Output> {
+        located(token, parse_socket_addr(token))
+    }
+}
+
+fn parse_socket_addr(token: &str) -> Malformed<SocketAddr> {
+    let (address, port) = match token.strip_prefix('[') {
+        Some(rest) => {
+            let (address, rest) = rest
+                .split_once(']')
+                .ok_or_else(|| malformed("missing `]` after the IPv6 address"))?;
+            let port = rest
+                .strip_prefix(':')
+                .ok_or_else(|| malformed("missing port"))?;
+            (IpAddr::V6(parse_ipv6(address)?), port)
+        }
+        None => {
+            let (address, port) = token
+                .rsplit_once(':')
+                .ok_or_else(|| malformed("missing port"))?;
+            if address.contains(':') {
+                return Err(malformed(
+                    "IPv6 addresses must be written in brackets, like `[::1]:8080`",
+                ));
+            }
+            (parse_ip(address)?, port)
+        }
+    };
+    Ok(SocketAddr::new(address, parse_port(port)?))
+}
+
+/// A range of IP addresses in CIDR notation, like `10.0.0.0/8`.
+#[derive(Debug, Clone, Copy, Eq, PartialEq, Hash)]
+pub struct IpNet {
+    address: IpAddr,
+    prefix: u8,
+}
+
+impl IpNet {
+    /// Fails if `prefix` is longer than the address.
+    pub fn new(address: IpAddr, prefix: u8) -> Option<Self> {
+        (prefix <= Self::max_prefix(address)).then_some(Self { address, prefix })
+    }
+
+    /// The address as written, which may have host bits set.
+    pub fn address(&self) -> IpAddr {
+        self.address
+    }
+
+    pub fn prefix(&self) -> u8 {
+        self.prefix
+    }
+
+    /// The first address of the range.
+    pub fn network(&self) -> IpAddr {
+        match self.address {
+            IpAddr::V4(address) => IpAddr::V4((u32::from(address) & self.mask() as u32).into()),
+            IpAddr::V6(address) => IpAddr::V6((u128::from(address) & self.mask()).into()),
+        }
+    }
+
+    pub fn contains(&self, address: IpAddr) -> bool {
+        match (self.address, address) {
+            (IpAddr::V4(network), IpAddr::V4(address)) => {
+                let mask = self.mask() as u32;
+                u32::from(network) & mask == u32::from(address) & mask
+            }
+            (IpAddr::V6(network), IpAddr::V6(address)) => {
+                let mask = self.mask();
+                u128::from(network) & mask == u128::from(address) & mask
+            }
+            _ => false,
+        }
+    }
+
+    fn max_prefix(address: IpAddr) -> u8 {
+        match address {
+            IpAddr::V4(_) => 32,
+            IpAddr::V6(_) => 128,
+        }
+    }
+
+    /// The prefix as a mask in the low bits of an `u128`.
+    fn mask(&self) -> u128 {
+        let all = u128::MAX >> (128 - u32::from(Self::max_prefix(self.address)));
+        all ^ all.checked_shr(u32::from(self.prefix)).unwrap_or(0)
+    }
+}
+
+impl Display for IpNet {
+    fn fmt(&self, f: &mut Formatter<'_>) -> std::fmt::Result {
+        write!(f, "{}/{}", self.address, self.prefix)
+    }
+}
+
+/// Parses a range of IP addresses, `10.0.0.0/8` or `2001:db8::/32`. A single address is a range
+/// of one.
+#[derive(Debug, Clone)]
+pub struct IpNetArgument;
+
+impl ArgumentParser for IpNetArgument {
+    type Output = IpNet;
+
+    fn type_name(&self) -> Cow<'static, str> {
+        "ip range".into()
+    }
+
+    fn parse(&self, token: &str) -> Result<Self::Output> {
+        located(token, parse_ip_net(token))
+    }
+}
+
+fn parse_ip_net(token: &str) -> Malformed<IpNet> {
+    let Some((address, prefix)) = token.split_once('/') else {
+        let address = parse_ip(token)?;
+        return Ok(IpNet {
+            address,
+            prefix: IpNet::max_prefix(address),
+        });
+    };
+    let address = parse_ip(address)?;
+    if prefix.is_empty() {
+        return Err(malformed("missing prefix length after `/`"));
+    }
+    let length = prefix
+        .parse()
+        .map_err(|_| malformed(format!("`{prefix}` is not a valid prefix length")))?;
+    IpNet::new(address, length).ok_or_else(|| {
+        malformed(format!(
+            "prefix length {length} is too long, expected at most {}",
+            IpNet::max_prefix(address)
+        ))
+    })
+}
+
+/// A URL split into its parts. The path, query and fragment are kept as written.
+#[derive(Debug, Clone, Eq, PartialEq, Hash)]
+pub struct Url {
+    /// Lowercase scheme, like `https`
+    pub scheme: String,
+    /// Host name or address, IPv6 addresses in brackets
+    pub host: String,
+    pub port: Option<u16>,
+    /// Path starting with `/`, or empty
+    pub path: String,
+    pub query: Option<String>,
+    pub fragment: Option<String>,
+}
+
+impl Display for Url {
+    fn fmt(&self, f: &mut Formatter<'_>) -> std::fmt::Result {
+        write!(f, "{}://{}", self.scheme, self.host)?;
+        if let Some(port) = self.port {
+            write!(f, ":{port}")?;
+        }
+        f.write_str(&self.path)?;
+        if let Some(query) = &self.query {
+            write!(f, "?{query}")?;
+        }
+        if let Some(fragment) = &self.fragment {
+            write!(f, "#{fragment}")?;
+        }
+        Ok(())
+    }
+}
+
+/// Parses an absolute URL with one of the allowed schemes, `http` and `https` by default.
+///
+/// Only the structure is checked, hosts are not resolved and user info is rejected.
+#[derive(Debug, Clone)]
+pub struct UrlArgument {
+    schemes: Vec<String>,
+}
+
+impl UrlArgument {
+    pub fn new() -> Self {
+        Self { schemes: vec![] }.schemes(["http", "https"])
+    }
+
+    /// Replaces the allowed schemes.
+    pub fn schemes<S: Into<String>>(mut self, schemes: impl IntoIterator<Item = S>) -> Self {
+        self.schemes = schemes
+            .into_iter()
+            .map(|scheme| scheme.into().to_ascii_lowercase())
+            .collect();
+        self
+    }
+
+    fn parse_url(&self, token: &str) -> Malformed<Url> {
+        let (scheme, rest) = token
+            .split_once("://")
+            .ok_or_else(|| malformed("missing scheme, like `https://`"))?;
+        let valid = scheme.starts_with(|c: char| c.is_ascii_alphabetic())
+            && scheme
+                .chars()
+                .all(|c| c.is_ascii_alphanumeric() || matches!(c, '+' | '-' | '.'));
+        if !valid {
+            return Err(malformed(format!("`{scheme}` is not a valid scheme")));
+        }
+        let scheme = scheme.to_ascii_lowercase();
+        if !self.schemes.contains(&scheme) {
+            return Err(malformed(format!(
+                "scheme `{scheme}` is not allowed, expected one of {}",
+                self.schemes.join(", ")
+            )));
+        }
+
+        let (rest, fragment) = match rest.split_once('#') {
+            Some((rest, fragment)) => (rest, Some(fragment.to_string())),
+            None => (rest, None),
+        };
+        let (rest, query) = match rest.split_once('?') {
+            Some((rest, query)) => (rest, Some(query.to_string())),
+            None => (rest, None),
+        };
+        let (authority, path) = rest.split_at(rest.find('/').unwrap_or(rest.len()));
+        let (host, port) = Self::parse_authority(authority)?;
+        Ok(Url {
+            scheme,
+            host,
+            port,
+            path: path.to_string(),
+            query,
+            fragment,
+        })
+    }
+
+    fn parse_authority(authority: &str) -> Malformed<(String, Option<u16>)> {
+        if authority.contains('@') {
+            return Err(malformed("user info is not allowed in the URL"));
+        }
+        let (host, port) = match authority.strip_prefix('[') {
+            Some(rest) => {
+                let (address, rest) = rest
+                    .split_once(']')
+                    .ok_or_else(|| malformed("missing `]` after the IPv6 address"))?;
+                parse_ipv6(address)?;
+                let port =
+                    match rest {
+                        "" => None,
+                        rest => Some(rest.strip_prefix(':').ok_or_else(|| {
+                            malformed(format!("unexpected `{rest}` after the host"))
+                        })?),
+                    };
+                (&authority[..address.len() + 2], port)
+            }
+            None => match authority.split_once(':') {
+                Some((host, port)) => (host, Some(port)),
+                None => (authority, None),
+            },
+        };
+
+        if host.is_empty() {
+            return Err(malformed("missing host"));
+        }
+        let valid = host.starts_with('[')
+            || host
+                .chars()
+                .all(|c| c.is_alphanumeric() || matches!(c, '-' | '.' | '_'));
+        if !valid {
+            return Err(malformed(format!("`{host}` is not a valid host")));
+        }
+        Ok((host.to_string(), port.map(parse_port).transpose()?))
+    }
+}
+
+impl Default for UrlArgument {
+    fn default() -> Self {
+        Self::new()
+    }
+}
+
+impl ArgumentParser for UrlArgument {
+    type Output = Url;
+
+    fn type_name(&self) -> Cow<'static, str> {
+        "url".into()
+    }
+
+    fn parse(&self, token: &str) -> Result<Self::Output> {
+        located(token, self.parse_url(token))
+    }
 
-    fn validator(&self) -> fn(&str) -> bool {
-        |_| true
+    /// Completes the allowed schemes.
+    fn suggest(&self, token: &str) -> Vec<String> {
+        let token = token.to_ascii_lowercase();
+        self.schemes
+            .iter()
+            .map(|scheme| format!("{scheme}://"))
+            .filter(|prefix| prefix.starts_with(&token))
+            .collect()
     }
 }
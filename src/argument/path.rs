@@ -0,0 +1,193 @@
+use crate::argument::parser::ArgumentParser;
+use crate::{Error, InvalidCommandReason, Result};
+use std::borrow::Cow;
+use std::path::{Component, Path, PathBuf};
+
+/// Kind of filesystem entry a [`PathArgument`] accepts.
+#[derive(Debug, Clone, Copy, Default, Eq, PartialEq)]
+pub enum PathKind {
+    #[default]
+    Any,
+    File,
+    Directory,
+}
+
+/// Parses a filesystem path.
+///
+/// With a [root](Self::root), relative paths are resolved against it and paths leaving it, like
+/// `../secret` or through a symbolic link, are rejected. The root itself must exist.
+#[derive(Debug, Clone, Default)]
+pub struct PathArgument {
+    must_exist: bool,
+    kind: PathKind,
+    extensions: Vec<String>,
+    root: Option<PathBuf>,
+}
+
+impl PathArgument {
+    pub fn new() -> Self {
+        Self::default()
+    }
+
+    /// Rejects paths that do not exist.
+    pub fn must_exist(mut self) -> Self {
+        self.must_exist = true;
+        self
+    }
+
+    /// Only accepts entries of `kind`. Paths that do not exist are only checked by their
+    /// extension.
+    pub fn kind(mut self, kind: PathKind) -> Self {
+        self.kind = kind;
+        self
+    }
+
+    /// Only accepts files with one of `extensions`, ignoring ASCII case.
+    pub fn extensions<S: Into<String>>(mut self, extensions: impl IntoIterator<Item = S>) -> Self {
+        self.extensions = extensions.into_iter().map(Into::into).collect();
+        self
+    }
+
+    /// Resolves relative paths against `root` and rejects paths outside of it.
+    pub fn root(mut self, root: impl Into<PathBuf>) -> Self {
+        self.root = Some(root.into());
+        self
+    }
+
+    /// Completes `token` with the matching entries of its directory, relative to `base`, such as
+    /// a working directory kept in the command context. With a root, a relative `base` is
+    /// resolved against it, and directories are resolved and checked like parsed paths.
+    /// Directories end with `/`.
+    pub fn complete(&self, base: &Path, token: &str) -> Vec<String> {
+        let (directory, name) = match token.rfind(['/', std::path::MAIN_SEPARATOR]) {
+            Some(index) => token.split_at(index + 1),
+            None => ("", token),
+        };
+        let Some(path) =
+            normalize(Path::new(directory)).and_then(|relative| self.resolve(base, &relative))
+        else {
+            return vec![];
+        };
+        let path = match path.as_os_str().is_empty() {
+            true => Path::new("."),
+            false => &path,
+        };
+        let Ok(entries) = std::fs::read_dir(path) else {
+            return vec![];
+        };
+
+        let mut candidates = vec![];
+        for entry in entries.flatten() {
+            let Ok(entry_name) = entry.file_name().into_string() else {
+                continue;
+            };
+            // hidden entries only when asked for
+            if !entry_name.starts_with(name)
+                || (entry_name.starts_with('.') && !name.starts_with('.'))
+            {
+                continue;
+            }
+            let path = entry.path();
+            if path.is_dir() {
+                candidates.push(format!("{directory}{entry_name}/"));
+            } else if self.kind != PathKind::Directory && self.has_extension(&path) {
+                candidates.push(format!("{directory}{entry_name}"));
+            }
+        }
+        candidates.sort();
+        candidates
+    }
+
+    fn has_extension(&self, path: &Path) -> bool {
+        self.extensions.is_empty()
+            || path
+                .extension()
+                .and_then(|extension| extension.to_str())
+                .is_some_and(|extension| {
+                    self.extensions
+                        .iter()
+                        .any(|allowed| allowed.eq_ignore_ascii_case(extension))
+                })
+    }
+
+    /// Resolves `path` against `base`, and both against the root if there is one, rejecting
+    /// paths outside of the root.
+    fn resolve(&self, base: &Path, path: &Path) -> Option<PathBuf> {
+        let Some(root) = &self.root else {
+            return Some(base.join(path));
+        };
+
+        let path = normalize(&root.join(base).join(normalize(path)?))?;
+        if !path.starts_with(root) {
+            return None;
+        }
+        // symbolic links may still lead outside, including links to paths that do not exist yet
+        let root = root.canonicalize().ok()?;
+        canonicalize_existing(&path)?
+            .starts_with(root)
+            .then_some(path)
+    }
+}
+
+impl ArgumentParser for PathArgument {
+    type Output = PathBuf;
+
+    fn type_name(&self) -> Cow<'static, str> {
+        "path".into()
+    }
+
+    fn parse(&self, token: &str) -> Result<Self::Output> {
+        let path = (!token.is_empty())
+            .then(|| self.resolve(Path::new(""), Path::new(token)))
+            .flatten()
+            .ok_or(Error::InvalidCommand(InvalidCommandReason::InvalidArgument))?;
+        let matches_kind = match self.kind {
+            PathKind::Any => true,
+            PathKind::File => path.is_file() || !path.exists(),
+            PathKind::Directory => path.is_dir() || !path.exists(),
+        };
+        let matches_extension = path.is_dir() || self.has_extension(&path);
+        if (self.must_exist && !path.exists()) || !matches_kind || !matches_extension {
+            return Err(Error::InvalidCommand(InvalidCommandReason::InvalidArgument));
+        }
+        Ok(path)
+    }
+
+    fn suggest(&self, token: &str) -> Vec<String> {
+        self.complete(Path::new(""), token)
+    }
+}
+
+/// Canonicalizes the nearest existing ancestor of `path` and appends the rest of it. Fails on
+/// broken symbolic links, whose targets could be created outside of the root.
+fn canonicalize_existing(path: &Path) -> Option<PathBuf> {
+    for ancestor in path.ancestors() {
+        match ancestor.canonicalize() {
+            Ok(canonical) => return Some(canonical.join(path.strip_prefix(ancestor).ok()?)),
+            Err(_) if ancestor.symlink_metadata().is_ok() => return None,
+            Err(_) => {}
+        }
+    }
+    None
+}
+
+/// Removes `.` and `..` components, failing if a relative path goes above where it starts.
+fn normalize(path: &Path) -> Option<PathBuf> {
+    let mut normalized = PathBuf::new();
+    let mut depth = 0usize;
+    for component in path.components() {
+        match component {
+            Component::CurDir => {}
+            Component::ParentDir => {
+                depth = depth.checked_sub(1)?;
+                normalized.pop();
+            }
+            Component::Normal(name) => {
+                depth += 1;
+                normalized.push(name);
+            }
+            Component::RootDir | Component::Prefix(_) => normalized.push(component),
+        }
+    }
+    Some(normalized)
+}
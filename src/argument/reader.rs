@@ -0,0 +1,141 @@
+use crate::{Error, InvalidCommandReason, Result};
+
+/// Cursor over the raw input of a command, for arguments that are not a single token.
+///
+/// Positions are byte offsets into the whole input, so they can be compared to spans.
+#[derive(Debug, Clone)]
+pub struct StringReader<'a> {
+    input: &'a str,
+    /// Where reading started, the reader never moves before it
+    start: usize,
+    position: usize,
+}
+
+impl<'a> StringReader<'a> {
+    pub fn new(input: &'a str, position: usize) -> Self {
+        Self {
+            input,
+            start: position,
+            position,
+        }
+    }
+
+    pub fn position(&self) -> usize {
+        self.position
+    }
+
+    /// Moves to `position`, failing if it is before the start of the reader, past the end of the
+    /// input or not on a character boundary.
+    pub fn set_position(&mut self, position: usize) -> Result<()> {
+        if position < self.start || !self.input.is_char_boundary(position) {
+            return Err(Error::InvalidCommand(InvalidCommandReason::InvalidArgument));
+        }
+        self.position = position;
+        Ok(())
+    }
+
+    /// The input left to read.
+    pub fn remaining(&self) -> &'a str {
+        &self.input[self.position..]
+    }
+
+    pub fn can_read(&self) -> bool {
+        self.position < self.input.len()
+    }
+
+    pub fn peek(&self) -> Option<char> {
+        self.remaining().chars().next()
+    }
+
+    pub fn read_char(&mut self) -> Option<char> {
+        let c = self.peek()?;
+        self.position += c.len_utf8();
+        Some(c)
+    }
+
+    /// Reads `expected`, failing if the input continues with anything else.
+    pub fn expect(&mut self, expected: char) -> Result<()> {
+        match self.peek() {
+            Some(c) if c == expected => {
+                self.position += c.len_utf8();
+                Ok(())
+            }
+            _ => Err(Error::InvalidCommand(InvalidCommandReason::InvalidArgument)),
+        }
+    }
+
+    /// Reads while `predicate` holds, returning the text read.
+    pub fn read_while(&mut self, predicate: impl Fn(char) -> bool) -> &'a str {
+        let remaining = self.remaining();
+        let length = remaining
+            .find(|c: char| !predicate(c))
+            .unwrap_or(remaining.len());
+        self.position += length;
+        &remaining[..length]
+    }
+
+    pub fn skip_whitespace(&mut self) -> &'a str {
+        self.read_while(char::is_whitespace)
+    }
+
+    /// Reads up to whitespace or the end of the command, failing if that is empty.
+    pub fn read_word(&mut self) -> Result<&'a str> {
+        let word = self.read_while(|c| !c.is_whitespace() && c != ';');
+        match word.is_empty() {
+            true => Err(Error::InvalidCommand(InvalidCommandReason::InvalidArgument)),
+            false => Ok(word),
+        }
+    }
+
+    /// Reads a string quoted with the current character, up to the closing quote, where `\`
+    /// escapes the next character. Returns the string with its quotes.
+    pub fn read_quoted(&mut self) -> Result<&'a str> {
+        let start = self.position;
+        let quote = self
+            .read_char()
+            .ok_or(Error::InvalidCommand(InvalidCommandReason::InvalidArgument))?;
+        while let Some(c) = self.read_char() {
+            match c {
+                '\\' => {
+                    self.read_char();
+                }
+                c if c == quote => return Ok(&self.input[start..self.position]),
+                _ => {}
+            }
+        }
+        self.position = start;
+        Err(Error::InvalidCommand(InvalidCommandReason::InvalidArgument))
+    }
+
+    /// Reads a value in brackets, `{...}` or `[...]`, up to the bracket closing the first one.
+    /// Brackets are skipped in strings quoted with one of `quotes`, where `\` escapes the next
+    /// character.
+    pub fn read_bracketed(&mut self, quotes: &[char]) -> Result<&'a str> {
+        let start = self.position;
+        if !matches!(self.peek(), Some('{' | '[')) {
+            return Err(Error::InvalidCommand(InvalidCommandReason::InvalidArgument));
+        }
+
+        let mut depth = 0usize;
+        let mut quote = None;
+        while let Some(c) = self.read_char() {
+            match (quote, c) {
+                (Some(_), '\\') => {
+                    self.read_char();
+                }
+                (Some(open), c) if c == open => quote = None,
+                (Some(_), _) => {}
+                (None, '{' | '[') => depth += 1,
+                (None, '}' | ']') => {
+                    depth -= 1;
+                    if depth == 0 {
+                        return Ok(&self.input[start..self.position]);
+                    }
+                }
+                (None, c) if quotes.contains(&c) => quote = Some(c),
+                (None, _) => {}
+            }
+        }
+        Err(Error::InvalidCommand(InvalidCommandReason::InvalidArgument))
+    }
+}
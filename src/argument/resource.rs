@@ -0,0 +1,124 @@
+use crate::argument::parser::ArgumentParser;
+use crate::parsers::tokenize::Span;
+use crate::{Error, InvalidCommandReason, Result};
+use std::borrow::{Borrow, Cow};
+use std::fmt::{Display, Formatter};
+
+/// A namespaced identifier, `namespace:path`.
+#[derive(Debug, Clone, Eq, PartialEq, Ord, PartialOrd, Hash)]
+pub struct ResourceId {
+    pub namespace: String,
+    pub path: String,
+}
+
+impl ResourceId {
+    pub fn new(namespace: impl Into<String>, path: impl Into<String>) -> Self {
+        Self {
+            namespace: namespace.into(),
+            path: path.into(),
+        }
+    }
+}
+
+impl Display for ResourceId {
+    fn fmt(&self, f: &mut Formatter<'_>) -> std::fmt::Result {
+        write!(f, "{}:{}", self.namespace, self.path)
+    }
+}
+
+/// Parses a [`ResourceId`], using the default namespace if the id has none.
+///
+/// Namespaces may contain `a-z`, `0-9`, `_`, `-` and `.`, paths may also contain `/`.
+#[derive(Debug, Clone)]
+pub struct ResourceIdArgument {
+    default_namespace: String,
+}
+
+impl ResourceIdArgument {
+    pub fn new(default_namespace: impl Into<String>) -> Self {
+        Self {
+            default_namespace: default_namespace.into(),
+        }
+    }
+
+    /// Completes `token` with the ids of `registry`, usually kept in the command context.
+    ///
+    /// Before a `:` is typed, the token is matched against both namespaces and the paths of the
+    /// default namespace, which are suggested without it.
+    pub fn complete<R: Borrow<ResourceId>>(
+        &self,
+        registry: impl IntoIterator<Item = R>,
+        token: &str,
+    ) -> Vec<String> {
+        let mut candidates = vec![];
+        for id in registry {
+            let id = id.borrow();
+            match token.split_once(':') {
+                Some((namespace, path)) => {
+                    if id.namespace == namespace && id.path.starts_with(path) {
+                        candidates.push(id.to_string());
+                    }
+                }
+                None => {
+                    if id.namespace == self.default_namespace && id.path.starts_with(token) {
+                        candidates.push(id.path.clone());
+                    }
+                    if id.namespace.starts_with(token) {
+                        candidates.push(id.to_string());
+                    }
+                }
+            }
+        }
+        candidates.sort();
+        candidates.dedup();
+        candidates
+    }
+}
+
+impl Default for ResourceIdArgument {
+    /// Uses the `minecraft` namespace by default.
+    fn default() -> Self {
+        Self::new("minecraft")
+    }
+}
+
+impl ArgumentParser for ResourceIdArgument {
+    type Output = ResourceId;
+
+    fn type_name(&self) -> Cow<'static, str> {
+        "resource id".into()
+    }
+
+    fn parse(&self, token: &str) -> Result<Self::Output> {
+        let (namespace, path, path_start) = match token.split_once(':') {
+            Some((namespace, path)) => {
+                check(namespace, 0, "namespace", |c| c != '/')?;
+                (namespace, path, namespace.len() + 1)
+            }
+            None => (self.default_namespace.as_str(), token, 0),
+        };
+        check(path, path_start, "path", |_| true)?;
+        Ok(ResourceId::new(namespace, path))
+    }
+}
+
+/// Checks that `part`, `offset` bytes into the id, is not empty and only contains allowed
+/// characters.
+fn check(part: &str, offset: usize, name: &str, allowed: impl Fn(char) -> bool) -> Result<()> {
+    let invalid = part.char_indices().find(|(_, c)| {
+        !(c.is_ascii_lowercase() || c.is_ascii_digit() || matches!(c, '_' | '-' | '.' | '/'))
+            || !allowed(*c)
+    });
+    let (message, span) = match invalid {
+        Some((index, c)) => (
+            format!("invalid character `{c}` in {name}"),
+            Span::new(offset + index, offset + index + c.len_utf8()),
+        ),
+        None if part.is_empty() => (format!("missing {name}"), Span::new(offset, offset)),
+        None => return Ok(()),
+    };
+    Err(Error::InvalidCommand(InvalidCommandReason::SyntaxError {
+        message,
+        span,
+    }))
+}
@@ -0,0 +1,45 @@
+use crate::argument::parser::ArgumentParser;
+use crate::argument::reader::StringReader;
+use crate::parsers::snbt::parse_snbt;
+pub use crate::parsers::snbt::Nbt;
+use crate::Result;
+use std::borrow::Cow;
+
+/// Parses Minecraft's stringified NBT, like `{Count:1b,id:"minecraft:stone"}`, into an [`Nbt`]
+/// value.
+///
+/// Compounds and lists are read from the input up to their closing bracket, spaces included. Like
+/// [`JsonArgument`](crate::argument::json::JsonArgument), any balanced value is accepted while
+/// resolving the command and malformed SNBT is reported by
+/// [`ExecContext::get`](crate::ExecContext::get) as a
+/// [`SyntaxError`](crate::InvalidCommandReason::SyntaxError) located in the input.
+#[derive(Debug, Clone, Default)]
+pub struct SnbtArgument;
+
+impl ArgumentParser for SnbtArgument {
+    type Output = Nbt;
+
+    fn type_name(&self) -> Cow<'static, str> {
+        "snbt".into()
+    }
+
+    fn parse(&self, token: &str) -> Result<Self::Output> {
+        parse_snbt(token)
+    }
+
+    fn validate(&self, _token: &str) -> bool {
+        true
+    }
+
+    fn reads_input(&self) -> bool {
+        true
+    }
+
+    fn read(&self, reader: &mut StringReader) -> Result<()> {
+        match reader.peek() {
+            Some('{' | '[') => reader.read_bracketed(&['"', '\'']).map(|_| ()),
+            Some('"' | '\'') => reader.read_quoted().map(|_| ()),
+            _ => reader.read_word().map(|_| ()),
+        }
+    }
+}
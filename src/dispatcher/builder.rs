@@ -1,14 +1,26 @@
-use super::{ExecContext, NodeType};
+use super::history::History;
+use super::index::LiteralIndex;
+use super::{
+    Clock, ExecContext, Executor, LiteralMatching, Middleware, NodeType, RateLimit, Suggestions,
+    SystemClock,
+};
 use crate::argument::parser::ArgumentParser;
 use crate::argument::Argument;
+use crate::dispatcher::cooldown::Usage;
+use crate::parsers::tokenize::Dialect;
 use crate::{Command, Dispatcher, Error, Result};
 use std::fmt::Debug;
+use std::sync::Mutex;
+use std::time::Duration;
 
 #[allow(clippy::type_complexity)]
 pub struct CommandBuilder<C: Debug, O> {
     children: Vec<Command<C, O>>,
     node: NodeType,
-    exec: Option<fn(&mut ExecContext<C>) -> Result<O>>,
+    matching: Option<LiteralMatching>,
+    cooldown: Option<Duration>,
+    exec: Option<Executor<C, O>>,
+    suggestions: Option<Suggestions<C>>,
 }
 
 impl<C: Debug, O> CommandBuilder<C, O> {
@@ -16,12 +28,19 @@ impl<C: Debug, O> CommandBuilder<C, O> {
         Self {
             children: vec![],
             node: NodeType::Literal(name.into()),
+            matching: None,
+            cooldown: None,
             exec: None,
+            suggestions: None,
         }
     }
 
-    pub fn argument(parser: impl ArgumentParser, name: impl Into<String>, required: bool) -> Self {
-        Self::argument_validator(parser.validator(), name, required)
+    pub fn argument(
+        parser: impl ArgumentParser + Send + Sync + 'static,
+        name: impl Into<String>,
+        required: bool,
+    ) -> Self {
+        Self::from_argument(Argument::with_parser(parser, name.into(), required))
     }
 
     pub fn argument_validator(
@@ -29,15 +48,22 @@ impl<C: Debug, O> CommandBuilder<C, O> {
         name: impl Into<String>,
         required: bool,
     ) -> Self {
+        Self::from_argument(Argument::new(validator, name.into(), required))
+    }
+
+    fn from_argument(argument: Argument) -> Self {
         Self {
             children: vec![],
             exec: None,
-            node: NodeType::Argument(Argument::new(validator, name.into(), required)),
+            node: NodeType::Argument(argument),
+            matching: None,
+            cooldown: None,
+            suggestions: None,
         }
     }
 
     pub fn exec(mut self, exec: fn(&mut ExecContext<C>) -> Result<O>) -> Self {
-        self.exec = Some(exec);
+        self.exec = Some(Executor::Handler(exec));
         self
     }
 
@@ -46,37 +72,111 @@ impl<C: Debug, O> CommandBuilder<C, O> {
         self
     }
 
+    /// Overrides the dispatcher's literal matching policy for this node.
+    pub fn matching(mut self, matching: LiteralMatching) -> Self {
+        self.matching = Some(matching);
+        self
+    }
+
+    /// Completes this argument from the command context, instead of the suggestions of its parser.
+    pub fn suggestions(
+        mut self,
+        suggestions: impl Fn(&C, &str) -> Vec<String> + Send + Sync + 'static,
+    ) -> Self {
+        self.suggestions = Some(Box::new(suggestions));
+        self
+    }
+
+    /// Prevents a sender from running this command again until `cooldown` has passed.
+    pub fn cooldown(mut self, cooldown: Duration) -> Self {
+        self.cooldown = Some(cooldown);
+        self
+    }
+
     pub fn build(self) -> Command<C, O> {
         let (mut literals, arguments): (Vec<_>, Vec<_>) =
             self.children.into_iter().partition(|c| c.is_literal());
         literals.extend(arguments);
         Command {
+            index: LiteralIndex::new(&literals),
             children: literals,
             node: self.node,
+            matching: self.matching,
+            cooldown: self.cooldown,
             exec: self.exec,
+            suggestions: self.suggestions,
         }
     }
 }
 
 pub struct DispatcherBuilder<C: Debug, O, B> {
     root: CommandBuilder<C, O>,
-    prefix: Option<String>,
+    prefixes: Vec<String>,
+    prefix_resolver: Option<fn(&B) -> Vec<String>>,
+    matching: LiteralMatching,
+    abbreviations: bool,
+    dialect: Dialect,
     context_factory: Option<fn(&B) -> C>,
     base_context: Option<B>,
+    sender: Option<fn(&C) -> String>,
+    rate_limit: Option<RateLimit>,
+    clock: Box<dyn Clock>,
+    middleware: Vec<Box<dyn Middleware<C, O>>>,
+    history: Option<usize>,
 }
 
 impl<C: Debug, O, B> DispatcherBuilder<C, O, B> {
     pub fn new() -> Self {
         Self {
             root: CommandBuilder::literal(""),
-            prefix: None,
+            prefixes: vec![],
+            prefix_resolver: None,
+            matching: LiteralMatching::default(),
+            abbreviations: false,
+            dialect: Dialect::default(),
             context_factory: None,
             base_context: None,
+            sender: None,
+            rate_limit: None,
+            clock: Box::new(SystemClock),
+            middleware: vec![],
+            history: None,
         }
     }
 
+    /// Adds a command prefix. Commands must start with one of the prefixes, if any are set.
     pub fn prefix(mut self, prefix: impl Into<String>) -> Self {
-        self.prefix = Some(prefix.into());
+        self.prefixes.push(prefix.into());
+        self
+    }
+
+    pub fn prefixes<S: Into<String>>(mut self, prefixes: impl IntoIterator<Item = S>) -> Self {
+        self.prefixes.extend(prefixes.into_iter().map(Into::into));
+        self
+    }
+
+    /// Resolves additional prefixes from the base context each time a command is run.
+    pub fn prefix_resolver(mut self, resolver: fn(&B) -> Vec<String>) -> Self {
+        self.prefix_resolver = Some(resolver);
+        self
+    }
+
+    /// Sets the policy used to match literals, defaults to case-sensitive.
+    pub fn matching(mut self, matching: LiteralMatching) -> Self {
+        self.matching = matching;
+        self
+    }
+
+    /// Accepts any prefix of a literal that is unambiguous among its siblings, so `sh int` can
+    /// stand for `show interfaces`.
+    pub fn abbreviations(mut self, enabled: bool) -> Self {
+        self.abbreviations = enabled;
+        self
+    }
+
+    /// Sets the string syntax accepted in commands, see [`Dialect`].
+    pub fn dialect(mut self, dialect: Dialect) -> Self {
+        self.dialect = dialect;
         self
     }
 
@@ -90,6 +190,36 @@ impl<C: Debug, O, B> DispatcherBuilder<C, O, B> {
         self
     }
 
+    /// Identifies the sender of a command, used to key cooldowns and rate limits.
+    pub fn sender(mut self, sender: fn(&C) -> String) -> Self {
+        self.sender = Some(sender);
+        self
+    }
+
+    /// Limits each sender to `count` commands per `period`.
+    ///
+    /// # Panics
+    ///
+    /// Panics if `count` is zero, which would forbid every command.
+    pub fn rate_limit(mut self, count: usize, period: Duration) -> Self {
+        assert!(count > 0, "rate limit count must be at least 1");
+        self.rate_limit = Some(RateLimit { count, period });
+        self
+    }
+
+    /// Sets the clock used for cooldowns and rate limits, defaults to [`SystemClock`].
+    pub fn clock(mut self, clock: impl Clock + 'static) -> Self {
+        self.clock = Box::new(clock);
+        self
+    }
+
+    /// Adds a middleware around command execution. Middleware runs in the order it was added,
+    /// the first one being the outermost.
+    pub fn middleware(mut self, middleware: impl Middleware<C, O> + 'static) -> Self {
+        self.middleware.push(Box::new(middleware));
+        self
+    }
+
     pub fn child(mut self, child: impl Into<Command<C, O>>) -> Self {
         self.root.children.push(child.into());
         self
@@ -98,20 +228,67 @@ impl<C: Debug, O, B> DispatcherBuilder<C, O, B> {
     pub fn build(self) -> Result<Dispatcher<C, O, B>> {
         Ok(Dispatcher {
             root: self.root.build(),
-            prefix: self.prefix.unwrap_or_default(),
+            prefixes: self.prefixes,
+            prefix_resolver: self.prefix_resolver,
+            matching: self.matching,
+            abbreviations: self.abbreviations,
+            dialect: self.dialect,
             context_factory: self.context_factory.ok_or(Error::IncompleteBuilder)?,
             base_context: self.base_context.ok_or(Error::IncompleteBuilder)?,
+            sender: self.sender,
+            rate_limit: self.rate_limit,
+            clock: self.clock,
+            usage: Mutex::new(Usage::default()),
+            middleware: self.middleware,
+            history: self.history.map(|limit| Mutex::new(History::new(limit))),
         })
     }
 }
 
+impl<C: Debug, O: Default, B> DispatcherBuilder<C, O, B> {
+    /// Keeps the last `limit` undoable commands of each sender and adds the built-in `undo` and
+    /// `redo` commands. Undo outputs `O::default()`, redo outputs the result of the replayed
+    /// command.
+    pub fn undo(mut self, limit: usize) -> Self {
+        self.history = Some(limit);
+        self.root.children.push(Command {
+            children: vec![],
+            index: LiteralIndex::default(),
+            node: NodeType::Literal("undo".to_string()),
+            matching: None,
+            cooldown: None,
+            exec: Some(Executor::Undo(O::default)),
+            suggestions: None,
+        });
+        self.root.children.push(Command {
+            children: vec![],
+            index: LiteralIndex::default(),
+            node: NodeType::Literal("redo".to_string()),
+            matching: None,
+            cooldown: None,
+            exec: Some(Executor::Redo),
+            suggestions: None,
+        });
+        self
+    }
+}
+
 impl<C: Debug, O, B> Default for DispatcherBuilder<C, O, B> {
     fn default() -> Self {
         Self {
             root: CommandBuilder::literal(""),
-            prefix: None,
+            prefixes: vec![],
+            prefix_resolver: None,
+            matching: LiteralMatching::default(),
+            abbreviations: false,
+            dialect: Dialect::default(),
             context_factory: None,
             base_context: None,
+            sender: None,
+            rate_limit: None,
+            clock: Box::new(SystemClock),
+            middleware: vec![],
+            history: None,
         }
     }
 }
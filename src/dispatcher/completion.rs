@@ -0,0 +1,168 @@
+use super::{split_tokens, Command, Cursor, ExecContext, MatchOptions, NodeType, Segment};
+use crate::parsers::tokenize::{is_incomplete, tokenize, Token};
+use crate::Dispatcher;
+use std::fmt::Debug;
+
+/// Candidates for completing the last word of an input.
+#[derive(Debug, Clone, Default, Eq, PartialEq)]
+pub struct Completions {
+    /// Byte offset in the input where the completed word starts
+    pub start: usize,
+    pub candidates: Vec<String>,
+}
+
+impl<C: Debug, O, B> Dispatcher<C, O, B> {
+    /// Completes the last word of `input` from the literals of the command tree and the
+    /// suggestions of argument parsers.
+    pub fn complete(&self, input: &str) -> Completions {
+        self.complete_in_ctx(input, None)
+    }
+
+    pub fn complete_in_context(&self, input: &str, context: Box<dyn Fn(&B) -> C>) -> Completions {
+        self.complete_in_ctx(input, Some(&*context))
+    }
+
+    /// Whether `input` ends inside a quoted string and needs more lines to be complete.
+    pub fn is_incomplete(&self, input: &str) -> bool {
+        is_incomplete(input, self.dialect)
+    }
+
+    pub(crate) fn complete_in_ctx(
+        &self,
+        input: &str,
+        context: Option<&dyn Fn(&B) -> C>,
+    ) -> Completions {
+        let context = match context {
+            Some(factory) => factory(&self.base_context),
+            None => (self.context_factory)(&self.base_context),
+        };
+        let trimmed = input.trim_start();
+        let Some(command) = self.strip_prefix(trimmed) else {
+            return Completions {
+                start: input.len() - trimmed.len(),
+                candidates: self
+                    .prefixes
+                    .iter()
+                    .filter(|prefix| prefix.starts_with(trimmed))
+                    .cloned()
+                    .collect(),
+            };
+        };
+
+        let offset = input.len() - command.len();
+        let mut tokens = match tokenize(command, self.dialect) {
+            Ok((_, tokens)) => tokens
+                .into_iter()
+                .map(|token| token.shift(offset))
+                .collect::<Vec<_>>(),
+            Err(_) => return Completions::default(),
+        };
+        if let Some(end) = tokens
+            .iter()
+            .rposition(|token| matches!(token, Token::End(_)))
+        {
+            tokens.drain(..=end);
+        }
+
+        // the word under the cursor, unless the input ends with whitespace
+        let (start, partial) = match tokens.last() {
+            Some(Token::Simple(token)) if token.span.end == input.len() => {
+                (token.span.start, token.value.to_string())
+            }
+            Some(Token::Named(_, value)) if value.span.end == input.len() => {
+                return Completions {
+                    start: input.len(),
+                    candidates: vec![],
+                }
+            }
+            _ => (input.len(), String::new()),
+        };
+        if start < input.len() {
+            tokens.pop();
+        }
+
+        let (positional, named) = split_tokens(&tokens);
+        let segment = Segment {
+            input,
+            positional: &positional,
+            named: &named,
+            overrun: None,
+        };
+
+        let options = MatchOptions {
+            matching: self.matching,
+            abbreviations: self.abbreviations,
+        };
+        let mut context = ExecContext::new(context);
+        let mut nodes = vec![];
+        self.root.reachable(
+            Cursor::default(),
+            segment,
+            &mut context,
+            options,
+            &mut nodes,
+        );
+
+        let mut candidates = vec![];
+        for node in nodes {
+            for child in &node.children {
+                match &child.node {
+                    NodeType::Literal(name) => {
+                        let matching = child.matching.unwrap_or(options.matching);
+                        if matching
+                            .normalize(name)
+                            .starts_with(matching.normalize(&partial).as_ref())
+                        {
+                            candidates.push(name.clone());
+                        }
+                    }
+                    NodeType::Argument(argument) => match &child.suggestions {
+                        Some(suggestions) => {
+                            candidates.extend(suggestions(context.context(), &partial))
+                        }
+                        None => candidates.extend(argument.suggest(&partial)),
+                    },
+                }
+            }
+        }
+        candidates.sort();
+        candidates.dedup();
+
+        Completions { start, candidates }
+    }
+}
+
+impl<C: Debug, O> Command<C, O> {
+    /// Collects the nodes reached after consuming all `tokens`.
+    fn reachable<'a>(
+        &'a self,
+        cursor: Cursor,
+        segment: Segment<'a>,
+        context: &mut ExecContext<'a, C>,
+        options: MatchOptions,
+        nodes: &mut Vec<&'a Command<C, O>>,
+    ) {
+        let token = segment.token(cursor).map(|token| token.value);
+        if token.is_none() {
+            nodes.push(self);
+        }
+
+        let abbreviation = match (options.abbreviations, token) {
+            (true, Some(token)) => self.abbreviate(token, options.matching).unwrap_or(None),
+            _ => None,
+        };
+        for (index, child) in self.candidates(token, abbreviation) {
+            let mut cursor = cursor;
+            let processed = if abbreviation == Some(index) {
+                cursor = cursor.next();
+                true
+            } else {
+                child.process(&mut cursor, segment, context, options)
+            };
+            // optional arguments match without consuming tokens, so their children are reached too
+            if processed {
+                child.reachable(cursor, segment, context, options, nodes);
+            }
+        }
+    }
+}
@@ -0,0 +1,125 @@
+use std::collections::{HashMap, VecDeque};
+use std::sync::{Arc, Mutex};
+use std::time::{Duration, Instant};
+
+/// Source of time for cooldowns and rate limits.
+pub trait Clock: Send + Sync {
+    fn now(&self) -> Instant;
+}
+
+/// Wall clock time.
+#[derive(Debug, Clone, Copy, Default)]
+pub struct SystemClock;
+
+impl Clock for SystemClock {
+    fn now(&self) -> Instant {
+        Instant::now()
+    }
+}
+
+/// Clock that only moves when advanced, for tests.
+#[derive(Debug, Clone)]
+pub struct ManualClock(Arc<Mutex<Instant>>);
+
+impl ManualClock {
+    pub fn new() -> Self {
+        Self(Arc::new(Mutex::new(Instant::now())))
+    }
+
+    pub fn advance(&self, duration: Duration) {
+        *self.0.lock().unwrap() += duration;
+    }
+}
+
+impl Default for ManualClock {
+    fn default() -> Self {
+        Self::new()
+    }
+}
+
+impl Clock for ManualClock {
+    fn now(&self) -> Instant {
+        *self.0.lock().unwrap()
+    }
+}
+
+/// Maximum number of commands a sender may run within a period.
+#[derive(Debug, Clone, Copy, Eq, PartialEq)]
+pub struct RateLimit {
+    pub count: usize,
+    pub period: Duration,
+}
+
+#[derive(Default)]
+pub(crate) struct Usage {
+    /// Expiry of cooldowns per sender, keyed by command path
+    cooldowns: HashMap<String, HashMap<String, Instant>>,
+    /// Number of senders at which expired cooldowns are dropped
+    cooldowns_sweep_at: usize,
+    /// Recent executions per sender
+    executions: HashMap<String, VecDeque<Instant>>,
+    /// Number of senders at which idle ones are dropped from `executions`
+    sweep_at: usize,
+}
+
+impl Usage {
+    /// Returns the remaining wait time if `sender` is still on cooldown for `path`.
+    pub fn cooldown(&mut self, sender: &str, path: &str, now: Instant) -> Option<Duration> {
+        let paths = self.cooldowns.get_mut(sender)?;
+        match paths.get(path) {
+            Some(until) if *until > now => Some(*until - now),
+            Some(_) => {
+                paths.remove(path);
+                None
+            }
+            None => None,
+        }
+    }
+
+    pub fn start_cooldown(&mut self, sender: &str, path: &str, until: Instant, now: Instant) {
+        // Expired cooldowns of other senders are dropped like idle senders in `rate_limit`
+        if self.cooldowns.len() >= self.cooldowns_sweep_at {
+            self.cooldowns.retain(|_, paths| {
+                paths.retain(|_, until| *until > now);
+                !paths.is_empty()
+            });
+            self.cooldowns_sweep_at = (self.cooldowns.len() * 2).max(64);
+        }
+        match self.cooldowns.get_mut(sender) {
+            Some(paths) => paths.insert(path.to_string(), until),
+            None => self
+                .cooldowns
+                .entry(sender.to_string())
+                .or_default()
+                .insert(path.to_string(), until),
+        };
+    }
+
+    /// Records an execution, or returns the remaining wait time if the limit is reached.
+    pub fn rate_limit(&mut self, sender: &str, limit: RateLimit, now: Instant) -> Option<Duration> {
+        let expired = |times: &mut VecDeque<Instant>| {
+            while times
+                .front()
+                .is_some_and(|time| *time + limit.period <= now)
+            {
+                times.pop_front();
+            }
+            times.is_empty()
+        };
+        // Other senders are only pruned once their number doubled, so each command is O(1)
+        // amortized
+        if self.executions.len() >= self.sweep_at {
+            self.executions.retain(|_, times| !expired(times));
+            self.sweep_at = (self.executions.len() * 2).max(64);
+        }
+
+        let times = self.executions.entry(sender.to_string()).or_default();
+        expired(times);
+        if times.len() >= limit.count {
+            times.front().map(|oldest| *oldest + limit.period - now)
+        } else {
+            times.push_back(now);
+            None
+        }
+    }
+}
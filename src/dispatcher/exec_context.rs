@@ -1,39 +1,154 @@
+use super::history::UndoAction;
 use crate::argument::parser::ArgumentParser;
+use crate::parsers::tokenize::Span;
 use crate::{Error, InvalidCommandReason, Result};
-use fnv::FnvHashMap;
+use smallvec::SmallVec;
+use std::borrow::Cow;
 use std::fmt::Debug;
 
+/// A literal or argument matched in the input, with its raw value for arguments.
 #[derive(Debug)]
-pub struct ExecContext<C: Debug> {
-    arguments: FnvHashMap<String, String>,
+struct Binding<'a> {
+    name: Cow<'a, str>,
+    value: Option<Cow<'a, str>>,
+    span: Option<Span>,
+}
+
+/// State of a running command. Borrows from the dispatcher and the input, so resolving and
+/// running a typical command needs no heap allocation.
+#[derive(Debug)]
+pub struct ExecContext<'a, C: Debug> {
+    bindings: SmallVec<[Binding<'a>; 8]>,
+    path: SmallVec<[&'a str; 8]>,
+    undo: Option<UndoAction<C>>,
     context: C,
 }
 
-impl<C: Debug> ExecContext<C> {
+impl<'a, C: Debug> ExecContext<'a, C> {
     pub fn new(context: C) -> Self {
         Self {
-            arguments: FnvHashMap::default(),
+            bindings: SmallVec::new(),
+            path: SmallVec::new(),
+            undo: None,
             context,
         }
     }
 
-    pub fn get<A>(
-        &self,
-        name: impl Into<String>,
-        parser: impl ArgumentParser<Output = A>,
-    ) -> Result<A> {
-        if let Some(token) = self.arguments.get(&name.into()) {
-            parser.parse(token)
-        } else {
-            Err(Error::InvalidCommand(InvalidCommandReason::MissingArgument))
-        }
+    /// Parses the argument called `name`. The span of a [`SyntaxError`] is moved to the input, or
+    /// covers the whole argument if it was quoted.
+    ///
+    /// [`SyntaxError`]: InvalidCommandReason::SyntaxError
+    pub fn get<A>(&self, name: &str, parser: impl ArgumentParser<Output = A>) -> Result<A> {
+        let Some((token, span)) = self
+            .binding(name)
+            .and_then(|binding| Some((binding.value.as_deref()?, binding.span)))
+        else {
+            return Err(Error::InvalidCommand(InvalidCommandReason::MissingArgument));
+        };
+        parser.parse(token).map_err(|error| match (error, span) {
+            (
+                Error::InvalidCommand(InvalidCommandReason::SyntaxError {
+                    message,
+                    span: error,
+                }),
+                Some(argument),
+            ) => {
+                let span = match argument.end - argument.start == token.len() {
+                    true => Span::new(argument.start + error.start, argument.start + error.end),
+                    false => argument,
+                };
+                Error::InvalidCommand(InvalidCommandReason::SyntaxError { message, span })
+            }
+            (error, _) => error,
+        })
     }
 
     pub fn context(&self) -> &C {
         &self.context
     }
 
+    pub fn context_mut(&mut self) -> &mut C {
+        &mut self.context
+    }
+
+    /// Names of the literals and arguments leading to the executed command.
+    pub fn path(&self) -> &[&'a str] {
+        &self.path
+    }
+
+    /// Location in the input of the argument or literal called `name`.
+    pub fn span(&self, name: &str) -> Option<Span> {
+        self.binding(name).and_then(|binding| binding.span)
+    }
+
+    /// Raw values of the parsed arguments, by name.
+    pub fn arguments(&self) -> impl Iterator<Item = (&str, &str)> {
+        self.bindings.iter().filter_map(|binding| {
+            binding
+                .value
+                .as_deref()
+                .map(|value| (binding.name.as_ref(), value))
+        })
+    }
+
+    /// Registers the action reverting this command, run by the built-in `undo` command. Only
+    /// kept if the command succeeds and undo history is enabled on the dispatcher.
+    pub fn on_undo(
+        &mut self,
+        action: impl FnOnce(&mut ExecContext<C>) -> Result<()> + Send + 'static,
+    ) {
+        self.undo = Some(UndoAction::new(action));
+    }
+
+    pub(crate) fn take_undo(&mut self) -> Option<UndoAction<C>> {
+        self.undo.take()
+    }
+
+    pub(crate) fn into_context(self) -> C {
+        self.context
+    }
+
+    pub(crate) fn set_path(&mut self, path: impl IntoIterator<Item = &'a str>) {
+        self.path = path.into_iter().collect();
+        let path = &self.path;
+        self.bindings
+            .retain(|binding| path.iter().any(|segment| *segment == binding.name));
+    }
+
+    pub(crate) fn set_span(&mut self, name: &'a str, span: Span) {
+        self.binding_mut(Cow::Borrowed(name)).span = Some(span);
+    }
+
+    pub(crate) fn bind(&mut self, name: &'a str, value: &'a str, span: Span) {
+        let binding = self.binding_mut(Cow::Borrowed(name));
+        binding.value = Some(Cow::Borrowed(value));
+        binding.span = Some(span);
+    }
+
     pub fn insert_argument(&mut self, name: String, value: String) {
-        self.arguments.insert(name, value);
+        self.binding_mut(Cow::Owned(name)).value = Some(Cow::Owned(value));
+    }
+
+    fn binding(&self, name: &str) -> Option<&Binding<'a>> {
+        self.bindings.iter().find(|binding| binding.name == name)
+    }
+
+    fn binding_mut(&mut self, name: Cow<'a, str>) -> &mut Binding<'a> {
+        let index = match self
+            .bindings
+            .iter()
+            .position(|binding| binding.name == name)
+        {
+            Some(index) => index,
+            None => {
+                self.bindings.push(Binding {
+                    name,
+                    value: None,
+                    span: None,
+                });
+                self.bindings.len() - 1
+            }
+        };
+        &mut self.bindings[index]
     }
 }
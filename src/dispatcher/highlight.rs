@@ -0,0 +1,214 @@
+use super::{split_tokens, Command, Cursor, ExecContext, MatchOptions, NodeType, Segment};
+use crate::parsers::tokenize::{tokenize, Span, Token};
+use crate::Dispatcher;
+use std::fmt::Debug;
+use std::ops::Range;
+
+/// Classification of a part of the input, see [`Dispatcher::highlight`].
+#[derive(Debug, Clone, Eq, PartialEq)]
+pub enum HighlightKind {
+    Prefix,
+    Literal,
+    Argument {
+        name: String,
+        type_name: Option<String>,
+    },
+    /// Key of a named argument
+    NamedKey {
+        name: String,
+    },
+    /// Value of a named argument
+    NamedValue {
+        name: String,
+        type_name: Option<String>,
+    },
+    /// `;` between commands
+    Separator,
+    /// Text not matching the command tree
+    Error,
+}
+
+#[derive(Debug, Clone, Eq, PartialEq)]
+pub struct Highlight {
+    pub span: Span,
+    pub kind: HighlightKind,
+}
+
+impl Highlight {
+    fn new(span: Span, kind: HighlightKind) -> Self {
+        Self { span, kind }
+    }
+}
+
+impl<C: Debug, O, B> Dispatcher<C, O, B> {
+    /// Classifies the parts of a possibly incomplete `input` without executing it, following the
+    /// command tree as far as the input matches. Returns nothing if no prefix matches.
+    pub fn highlight(&self, input: &str) -> Vec<Highlight> {
+        let trimmed = input.trim_start();
+        let Some(command) = self.strip_prefix(trimmed) else {
+            return vec![];
+        };
+
+        let mut highlights = vec![];
+        let offset = input.len() - command.len();
+        if offset > input.len() - trimmed.len() {
+            highlights.push(Highlight::new(
+                Span::new(input.len() - trimmed.len(), offset),
+                HighlightKind::Prefix,
+            ));
+        }
+
+        let (remainder, tokens) = match tokenize(command, self.dialect) {
+            Ok(result) => result,
+            Err(_) => (command, vec![]),
+        };
+        let mut segment = vec![];
+        for token in tokens {
+            match token.shift(offset) {
+                Token::End(span) => {
+                    self.highlight_command(input, std::mem::take(&mut segment), &mut highlights);
+                    highlights.push(Highlight::new(span, HighlightKind::Separator));
+                }
+                token => segment.push(token),
+            }
+        }
+        self.highlight_command(input, segment, &mut highlights);
+
+        // text the tokenizer could not read
+        let remainder = remainder.trim_start();
+        if !remainder.trim_end().is_empty() {
+            let start = input.len() - remainder.len();
+            highlights.push(Highlight::new(
+                Span::new(start, start + remainder.trim_end().len()),
+                HighlightKind::Error,
+            ));
+        }
+        highlights
+    }
+
+    fn highlight_command(&self, input: &str, tokens: Vec<Token>, highlights: &mut Vec<Highlight>) {
+        let (positional, named) = split_tokens(&tokens);
+        let segment = Segment {
+            input,
+            positional: &positional,
+            named: &named,
+            overrun: None,
+        };
+        let keys = tokens.iter().filter_map(|token| match token {
+            Token::Named(key, _) => Some(key),
+            _ => None,
+        });
+
+        let options = MatchOptions {
+            matching: self.matching,
+            abbreviations: self.abbreviations,
+        };
+        let mut context = ExecContext::new((self.context_factory)(&self.base_context));
+        let (end, nodes) = self
+            .root
+            .trace(Cursor::default(), segment, &mut context, options);
+
+        let mut classified = vec![];
+        for (consumed, node) in &nodes {
+            let kind = match &node.node {
+                NodeType::Literal(_) => HighlightKind::Literal,
+                NodeType::Argument(argument) => HighlightKind::Argument {
+                    name: argument.name.clone(),
+                    type_name: argument.type_name().map(str::to_string),
+                },
+            };
+            for span in segment.spans(consumed.start, consumed.end) {
+                classified.push(Highlight::new(span, kind.clone()));
+            }
+        }
+        let rest = Cursor {
+            token: positional.len(),
+            consumed: 0,
+        };
+        for span in segment.spans(end, rest) {
+            classified.push(Highlight::new(span, HighlightKind::Error));
+        }
+
+        for key in keys {
+            let value = named
+                .iter()
+                .find(|(name, _)| *name == key.value)
+                .map(|(_, value)| value)
+                .expect("named argument was split from the tokens");
+            let argument = nodes.iter().find_map(|(_, node)| match &node.node {
+                NodeType::Argument(argument)
+                    if argument.name == key.value && argument.matches(value.value) =>
+                {
+                    Some(argument)
+                }
+                _ => None,
+            });
+            match argument {
+                Some(argument) => {
+                    classified.push(Highlight::new(
+                        key.span,
+                        HighlightKind::NamedKey {
+                            name: key.value.to_string(),
+                        },
+                    ));
+                    classified.push(Highlight::new(
+                        value.span,
+                        HighlightKind::NamedValue {
+                            name: key.value.to_string(),
+                            type_name: argument.type_name().map(str::to_string),
+                        },
+                    ));
+                }
+                None => {
+                    classified.push(Highlight::new(
+                        Span::new(key.span.start, value.span.end),
+                        HighlightKind::Error,
+                    ));
+                }
+            }
+        }
+
+        classified.sort_by_key(|highlight| highlight.span.start);
+        highlights.extend(classified);
+    }
+}
+
+impl<C: Debug, O> Command<C, O> {
+    /// Follows `tokens` as far as they match the tree. Returns where the match ends and the nodes
+    /// on the way, with the part of the tokens each one consumed.
+    #[allow(clippy::type_complexity)]
+    fn trace<'a>(
+        &'a self,
+        cursor: Cursor,
+        segment: Segment<'a>,
+        context: &mut ExecContext<'a, C>,
+        options: MatchOptions,
+    ) -> (Cursor, Vec<(Range<Cursor>, &'a Command<C, O>)>) {
+        let mut best = (cursor, vec![]);
+
+        let token = segment.token(cursor).map(|token| token.value);
+        let abbreviation = match (options.abbreviations, token) {
+            (true, Some(token)) => self.abbreviate(token, options.matching).unwrap_or(None),
+            _ => None,
+        };
+        for (index, child) in self.candidates(token, abbreviation) {
+            let mut next = cursor;
+            let processed = if abbreviation == Some(index) {
+                next = next.next();
+                true
+            } else {
+                child.process(&mut next, segment, context, options)
+            };
+            if !processed {
+                continue;
+            }
+
+            let (end, mut nodes) = child.trace(next, segment, context, options);
+            if end > best.0 || best.1.is_empty() {
+                nodes.insert(0, (cursor..next, child));
+                best = (end, nodes);
+            }
+        }
+        best
+    }
+}
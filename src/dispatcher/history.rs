@@ -0,0 +1,90 @@
+use super::ExecContext;
+use crate::parsers::tokenize::Token;
+use crate::Result;
+use std::collections::{HashMap, VecDeque};
+use std::fmt::{Debug, Formatter};
+
+type Action<C> = Box<dyn FnOnce(&mut ExecContext<C>) -> Result<()> + Send>;
+
+/// Action reverting a command, registered with [`ExecContext::on_undo`].
+pub struct UndoAction<C: Debug>(Action<C>);
+
+impl<C: Debug> UndoAction<C> {
+    pub(crate) fn new(
+        action: impl FnOnce(&mut ExecContext<C>) -> Result<()> + Send + 'static,
+    ) -> Self {
+        Self(Box::new(action))
+    }
+
+    pub(crate) fn run(self, context: &mut ExecContext<C>) -> Result<()> {
+        (self.0)(context)
+    }
+}
+
+impl<C: Debug> Debug for UndoAction<C> {
+    fn fmt(&self, f: &mut Formatter<'_>) -> std::fmt::Result {
+        f.write_str("UndoAction")
+    }
+}
+
+pub(crate) struct Entry<C: Debug> {
+    /// The command, replayed to redo it
+    pub input: String,
+    pub tokens: Vec<Token<'static>>,
+    pub undo: UndoAction<C>,
+}
+
+struct SenderHistory<C: Debug> {
+    undo: VecDeque<Entry<C>>,
+    redo: Vec<(String, Vec<Token<'static>>)>,
+}
+
+/// Bounded undo and redo stacks per sender.
+pub(crate) struct History<C: Debug> {
+    limit: usize,
+    senders: HashMap<String, SenderHistory<C>>,
+}
+
+impl<C: Debug> History<C> {
+    pub fn new(limit: usize) -> Self {
+        Self {
+            limit,
+            senders: HashMap::new(),
+        }
+    }
+
+    /// Records an executed command. Redoing a command keeps the remaining redo stack, any other
+    /// command clears it.
+    pub fn record(&mut self, sender: &str, entry: Entry<C>, redo: bool) {
+        let history = self
+            .senders
+            .entry(sender.to_string())
+            .or_insert_with(|| SenderHistory {
+                undo: VecDeque::new(),
+                redo: vec![],
+            });
+        if !redo {
+            history.redo.clear();
+        }
+        history.undo.push_back(entry);
+        while history.undo.len() > self.limit {
+            history.undo.pop_front();
+        }
+    }
+
+    /// Takes the most recent command to undo. It is only moved to the redo stack by
+    /// [`undone`](Self::undone) once its action succeeded.
+    pub fn undo(&mut self, sender: &str) -> Option<Entry<C>> {
+        self.senders.get_mut(sender)?.undo.pop_back()
+    }
+
+    pub fn undone(&mut self, sender: &str, input: String, tokens: Vec<Token<'static>>) {
+        if let Some(history) = self.senders.get_mut(sender) {
+            history.redo.push((input, tokens));
+        }
+    }
+
+    pub fn redo(&mut self, sender: &str) -> Option<(String, Vec<Token<'static>>)> {
+        self.senders.get_mut(sender)?.redo.pop()
+    }
+}
@@ -0,0 +1,71 @@
+use super::{Command, LiteralMatching, NodeType};
+use smallvec::SmallVec;
+use std::borrow::Cow;
+use std::collections::HashMap;
+use std::fmt::Debug;
+
+/// Literal children of a node by their folded name, so a token is matched with a hash lookup
+/// instead of comparing it to every sibling. A sorted copy of the keys lets completion find
+/// literals by prefix. Folding a token allocates unless it is lowercase ASCII.
+///
+/// Names are folded so that any two strings matching under some [`LiteralMatching`] share a key.
+/// The index therefore only narrows down the candidates, which are then compared using the
+/// policy in effect.
+#[derive(Debug, Default)]
+pub(crate) struct LiteralIndex {
+    keys: HashMap<String, SmallVec<[usize; 1]>>,
+    /// Folded names with their position, sorted by name
+    sorted: Vec<(String, usize)>,
+    /// Number of literal children, which come before the arguments
+    literals: usize,
+}
+
+impl LiteralIndex {
+    pub fn new<C: Debug, O>(children: &[Command<C, O>]) -> Self {
+        let mut index = Self::default();
+        for (position, child) in children.iter().enumerate() {
+            if let NodeType::Literal(name) = &child.node {
+                let key = key(name).into_owned();
+                index.sorted.push((key.clone(), position));
+                index.keys.entry(key).or_default().push(position);
+                index.literals += 1;
+            }
+        }
+        index.sorted.sort();
+        index
+    }
+
+    /// Literals which may match `token`.
+    pub fn get(&self, token: &str) -> &[usize] {
+        self.keys
+            .get(key(token).as_ref())
+            .map_or(&[], |positions| positions.as_slice())
+    }
+
+    /// Literals which may start with `prefix`, in no particular order.
+    pub fn prefixed<'a>(&'a self, prefix: &'a str) -> impl Iterator<Item = usize> + 'a {
+        let prefix = key(prefix);
+        let start = self
+            .sorted
+            .partition_point(|(key, _)| key.as_str() < prefix.as_ref());
+        self.sorted[start..]
+            .iter()
+            .take_while(move |(key, _)| key.starts_with(prefix.as_ref()))
+            .map(|(_, position)| *position)
+    }
+
+    /// Positions of the argument children.
+    pub fn arguments(&self, children: usize) -> std::ops::Range<usize> {
+        self.literals..children
+    }
+}
+
+/// Folds `value` like [`LiteralMatching::UnicodeCaseFold`], without allocating for lowercase
+/// ASCII.
+fn key(value: &str) -> Cow<'_, str> {
+    if value.is_ascii() {
+        LiteralMatching::AsciiCaseInsensitive.normalize(value)
+    } else {
+        LiteralMatching::UnicodeCaseFold.normalize(value)
+    }
+}
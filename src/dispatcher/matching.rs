@@ -0,0 +1,44 @@
+use caseless::Caseless;
+use std::borrow::Cow;
+use unicode_normalization::UnicodeNormalization;
+
+/// Policy used to compare literal nodes with input tokens.
+#[derive(Debug, Clone, Copy, Default, Eq, PartialEq, Hash)]
+pub enum LiteralMatching {
+    /// Exact byte equality
+    #[default]
+    CaseSensitive,
+    /// Ignores case of ASCII letters only
+    AsciiCaseInsensitive,
+    /// Full Unicode case folding on canonically normalised input
+    UnicodeCaseFold,
+}
+
+impl LiteralMatching {
+    pub fn matches(&self, literal: &str, token: &str) -> bool {
+        match self {
+            LiteralMatching::CaseSensitive => literal == token,
+            LiteralMatching::AsciiCaseInsensitive => literal.eq_ignore_ascii_case(token),
+            LiteralMatching::UnicodeCaseFold => {
+                caseless::canonical_caseless_match_str(literal, token)
+            }
+        }
+    }
+
+    /// Returns the form of `value` that is compared under this policy.
+    pub fn normalize<'a>(&self, value: &'a str) -> Cow<'a, str> {
+        match self {
+            LiteralMatching::CaseSensitive => Cow::Borrowed(value),
+            LiteralMatching::AsciiCaseInsensitive => {
+                if value.bytes().any(|b| b.is_ascii_uppercase()) {
+                    Cow::Owned(value.to_ascii_lowercase())
+                } else {
+                    Cow::Borrowed(value)
+                }
+            }
+            LiteralMatching::UnicodeCaseFold => {
+                Cow::Owned(value.chars().nfd().default_case_fold().nfd().collect())
+            }
+        }
+    }
+}
@@ -0,0 +1,45 @@
+use super::ExecContext;
+use crate::Result;
+use std::fmt::Debug;
+
+/// Wraps the execution of every command, see [`DispatcherBuilder::middleware`].
+///
+/// [`DispatcherBuilder::middleware`]: super::DispatcherBuilder::middleware
+pub trait Middleware<C: Debug, O>: Send + Sync {
+    /// Runs around a command. Call `next.run(context)` to continue the chain, or return early
+    /// to skip the command.
+    fn handle(&self, context: &mut ExecContext<C>, next: Next<'_, C, O>) -> Result<O>;
+}
+
+impl<C: Debug, O, F> Middleware<C, O> for F
+where
+    F: Fn(&mut ExecContext<C>, Next<'_, C, O>) -> Result<O> + Send + Sync,
+{
+    fn handle(&self, context: &mut ExecContext<C>, next: Next<'_, C, O>) -> Result<O> {
+        self(context, next)
+    }
+}
+
+/// The rest of the middleware chain, ending with the command itself.
+#[allow(clippy::type_complexity)]
+pub struct Next<'a, C: Debug, O> {
+    middleware: &'a [Box<dyn Middleware<C, O>>],
+    exec: &'a dyn Fn(&mut ExecContext<C>) -> Result<O>,
+}
+
+impl<'a, C: Debug, O> Next<'a, C, O> {
+    #[allow(clippy::type_complexity)]
+    pub(crate) fn new(
+        middleware: &'a [Box<dyn Middleware<C, O>>],
+        exec: &'a dyn Fn(&mut ExecContext<C>) -> Result<O>,
+    ) -> Self {
+        Self { middleware, exec }
+    }
+
+    pub fn run(self, context: &mut ExecContext<C>) -> Result<O> {
+        match self.middleware.split_first() {
+            Some((first, rest)) => first.handle(context, Next::new(rest, self.exec)),
+            None => (self.exec)(context),
+        }
+    }
+}
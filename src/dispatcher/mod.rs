@@ -1,39 +1,237 @@
 use crate::argument::parser::ArgumentParser;
+use crate::argument::reader::StringReader;
 use crate::argument::Argument;
-use crate::parsers::tokenize::{tokenize, Token};
+use crate::parsers::tokenize::{Dialect, Span, Spanned, Token, Tokens};
 use crate::{Error, InvalidCommandReason, Result};
 pub use builder::*;
+pub use completion::Completions;
+use cooldown::Usage;
+pub use cooldown::{Clock, ManualClock, RateLimit, SystemClock};
 pub use exec_context::ExecContext;
-use nom::bytes::complete::tag;
+pub use highlight::{Highlight, HighlightKind};
+pub use history::UndoAction;
+use history::{Entry, History};
+use index::LiteralIndex;
+pub use matching::LiteralMatching;
+pub use middleware::{Middleware, Next};
 use nom::character::complete::multispace0;
-use std::collections::HashMap;
+use smallvec::SmallVec;
+use std::cell::Cell;
 use std::fmt::Debug;
+use std::sync::Mutex;
+use std::time::Duration;
 
 mod builder;
+mod completion;
+mod cooldown;
 mod exec_context;
+mod highlight;
+mod history;
+mod index;
+mod matching;
+mod middleware;
+mod usage;
 
 pub enum NodeType {
     Argument(Argument),
     Literal(String),
 }
 
-enum ExecState<O> {
+#[derive(Clone, Copy)]
+struct MatchOptions {
+    matching: LiteralMatching,
+    abbreviations: bool,
+}
+
+enum ExecState<T> {
     Working,
-    Done(Result<O>),
+    Done(Result<T>),
+}
+
+/// Nodes leading to a command, kept inline for typical command depths.
+type Chain<'a, C, O> = SmallVec<[&'a Command<C, O>; 8]>;
+
+/// Named arguments of a command, by key.
+type NamedArguments<'t> = [(&'t str, Spanned<&'t str>)];
+
+/// The tokens of a single command, resolved against the tree.
+#[derive(Clone, Copy)]
+struct Segment<'a> {
+    /// The whole input, which spans point into
+    input: &'a str,
+    positional: &'a [Spanned<&'a str>],
+    named: &'a NamedArguments<'a>,
+    /// Furthest end of an argument read past the last token, when the command may be extended
+    /// with the tokens after the next `;`
+    overrun: Option<&'a Cell<Option<usize>>>,
+}
+
+/// Position in the positional tokens of a [`Segment`].
+#[derive(Debug, Clone, Copy, Default, Eq, PartialEq, Ord, PartialOrd)]
+struct Cursor {
+    token: usize,
+    /// Bytes of the token consumed by an argument that ended inside it
+    consumed: usize,
 }
 
 pub struct Dispatcher<C: Debug, O, B> {
     root: Command<C, O>,
-    prefix: String,
+    prefixes: Vec<String>,
+    prefix_resolver: Option<fn(&B) -> Vec<String>>,
+    matching: LiteralMatching,
+    abbreviations: bool,
+    dialect: Dialect,
     context_factory: fn(&B) -> C,
     base_context: B,
+    sender: Option<fn(&C) -> String>,
+    rate_limit: Option<RateLimit>,
+    clock: Box<dyn Clock>,
+    usage: Mutex<Usage>,
+    middleware: Vec<Box<dyn Middleware<C, O>>>,
+    history: Option<Mutex<History<C>>>,
 }
 
 #[allow(clippy::type_complexity)]
 pub struct Command<C: Debug, O> {
     children: Vec<Command<C, O>>,
+    index: LiteralIndex,
     node: NodeType,
-    exec: Option<fn(&mut ExecContext<C>) -> Result<O>>,
+    matching: Option<LiteralMatching>,
+    cooldown: Option<Duration>,
+    exec: Option<Executor<C, O>>,
+    suggestions: Option<Suggestions<C>>,
+}
+
+/// Completions for an argument computed from the command context.
+type Suggestions<C> = Box<dyn Fn(&C, &str) -> Vec<String> + Send + Sync>;
+
+enum Executor<C: Debug, O> {
+    Handler(fn(&mut ExecContext<C>) -> Result<O>),
+    /// Built-in undo command, with the output it returns
+    Undo(fn() -> O),
+    /// Built-in redo command
+    Redo,
+}
+
+impl<C: Debug, O> Clone for Executor<C, O> {
+    fn clone(&self) -> Self {
+        *self
+    }
+}
+
+impl<C: Debug, O> Copy for Executor<C, O> {}
+
+impl<'a> Segment<'a> {
+    /// The token at `cursor`, without the part consumed by a previous argument.
+    fn token(&self, cursor: Cursor) -> Option<Spanned<&'a str>> {
+        let token = self.positional.get(cursor.token)?;
+        Some(Spanned::new(
+            &token.value[cursor.consumed..],
+            Span::new(token.span.start + cursor.consumed, token.span.end),
+        ))
+    }
+
+    /// Reads an argument from the raw input, starting at `cursor`. Returns the text read and the
+    /// cursor after it. A token not written as is, quoted or with escapes, is read as a whole,
+    /// like the value of a named argument, unless the argument reads up to its end or past it,
+    /// like a list starting with a quoted element.
+    fn read(&self, argument: &Argument, cursor: Cursor) -> Option<(Spanned<&'a str>, Cursor)> {
+        let token = self.token(cursor)?;
+        let quoted =
+            cursor.consumed == 0 && self.input[token.span.start..token.span.end] != *token.value;
+        match self.read_raw(argument, cursor) {
+            Some((read, next)) if !quoted || read.span.end >= token.span.end => Some((read, next)),
+            _ if quoted => Some((token, cursor.next())),
+            _ => None,
+        }
+    }
+
+    /// Reads an argument from the raw input, starting at `cursor`, quotes included.
+    ///
+    /// The argument may end inside a token only if the token is written as is, without quotes or
+    /// escapes, so the rest of it can be matched next. An argument read past the last token, like
+    /// JSON with `;` in a string, is rejected and recorded in `overrun`.
+    fn read_raw(&self, argument: &Argument, cursor: Cursor) -> Option<(Spanned<&'a str>, Cursor)> {
+        let start = self.token(cursor)?.span.start;
+        let last = self.positional.last()?.span.end;
+        let limit = match self.overrun {
+            Some(_) => self.input.len(),
+            None => last,
+        };
+        let mut reader = StringReader::new(&self.input[..limit], start);
+        argument.read(&mut reader).ok()?;
+        // `read` may replace the reader, so its position is checked before slicing
+        let read = self.input[..limit].get(start..reader.position())?;
+        let end = start + read.trim_end().len();
+        if end > last {
+            if let Some(overrun) = self.overrun {
+                overrun.set(Some(overrun.get().map_or(end, |overrun| overrun.max(end))));
+            }
+            return None;
+        }
+        if end == start
+            || self
+                .named
+                .iter()
+                .any(|(_, value)| value.span.start < end && value.span.end > start)
+        {
+            return None;
+        }
+
+        let index = cursor.token
+            + self.positional[cursor.token..]
+                .iter()
+                .take_while(|token| token.span.start < end)
+                .count()
+            - 1;
+        let token = &self.positional[index];
+        let next = if token.span.end == end {
+            Cursor {
+                token: index + 1,
+                consumed: 0,
+            }
+        } else if token.span.end > end
+            && self.input[token.span.start..token.span.end] == *token.value
+        {
+            Cursor {
+                token: index,
+                consumed: end - token.span.start,
+            }
+        } else {
+            return None;
+        };
+        Some((
+            Spanned::new(&self.input[start..end], Span::new(start, end)),
+            next,
+        ))
+    }
+
+    /// Spans of the tokens from `from` up to `to`, split where an argument ended inside a token.
+    fn spans(&self, from: Cursor, to: Cursor) -> impl Iterator<Item = Span> + 'a {
+        let positional = self.positional;
+        (from.token..positional.len().min(to.token + 1)).filter_map(move |index| {
+            let span = positional[index].span;
+            let start = match index == from.token {
+                true => span.start + from.consumed,
+                false => span.start,
+            };
+            let end = match index == to.token {
+                true => span.start + to.consumed,
+                false => span.end,
+            };
+            (start < end).then(|| Span::new(start, end))
+        })
+    }
+}
+
+impl Cursor {
+    /// The start of the token after the current one.
+    fn next(self) -> Self {
+        Cursor {
+            token: self.token + 1,
+            consumed: 0,
+        }
+    }
 }
 
 impl<C: Debug, O> Command<C, O> {
@@ -43,51 +241,172 @@ impl<C: Debug, O> Command<C, O> {
 
     pub fn argument(
         name: impl Into<String>,
-        parser: impl ArgumentParser,
+        parser: impl ArgumentParser + Send + Sync + 'static,
         required: bool,
     ) -> CommandBuilder<C, O> {
         CommandBuilder::argument(parser, name, required)
     }
 
-    fn execute(
-        &self,
-        mut offset: usize,
-        tokens: &[String],
-        named_arguments: &mut HashMap<String, String>,
-        context: &mut ExecContext<C>,
-    ) -> ExecState<O> {
-        if offset >= tokens.len() {
-            return ExecState::Done(if let Some(exec) = &self.exec {
-                exec(context)
+    /// Finds the node to execute, returning the chain of nodes leading to it, starting from
+    /// the executed node.
+    fn resolve<'a>(
+        &'a self,
+        cursor: Cursor,
+        segment: Segment<'a>,
+        context: &mut ExecContext<'a, C>,
+        options: MatchOptions,
+    ) -> ExecState<Chain<'a, C, O>> {
+        let Some(token) = segment.token(cursor) else {
+            // arguments given by name may still follow the last token
+            for (_, child) in self.candidates(None, None) {
+                let named = match &child.node {
+                    NodeType::Argument(argument) => {
+                        segment.named.iter().any(|(key, _)| *key == argument.name)
+                    }
+                    NodeType::Literal(_) => false,
+                };
+                let mut cursor = cursor;
+                if named && child.process(&mut cursor, segment, context, options) {
+                    if let ExecState::Done(res) = child.resolve(cursor, segment, context, options) {
+                        return ExecState::Done(res.map(|mut chain| {
+                            chain.push(self);
+                            chain
+                        }));
+                    }
+                }
+            }
+            return ExecState::Done(if self.exec.is_some() {
+                Ok(smallvec::smallvec![self])
             } else {
                 Err(Error::InvalidCommand(InvalidCommandReason::UnknownCommand))
             });
+        };
+
+        let mut abbreviation = None;
+        let mut ambiguous = None;
+        if options.abbreviations {
+            match self.abbreviate(token.value, options.matching) {
+                Ok(index) => abbreviation = index,
+                Err(candidates) => ambiguous = Some(candidates),
+            }
         }
 
-        for child in &self.children {
-            if child.process(&mut offset, tokens, named_arguments, context) {
-                match child.execute(offset, tokens, named_arguments, context) {
+        for (index, child) in self.candidates(Some(token.value), abbreviation) {
+            let mut cursor = cursor;
+            let processed = if abbreviation == Some(index) {
+                context.set_span(child.name(), token.span);
+                cursor = cursor.next();
+                true
+            } else {
+                child.process(&mut cursor, segment, context, options)
+            };
+            if processed {
+                match child.resolve(cursor, segment, context, options) {
                     ExecState::Working => continue,
-                    ExecState::Done(res) => return ExecState::Done(res),
+                    ExecState::Done(res) => {
+                        return ExecState::Done(res.map(|mut chain| {
+                            chain.push(self);
+                            chain
+                        }))
+                    }
                 }
             }
         }
 
-        ExecState::Working
+        match ambiguous {
+            Some(candidates) => ExecState::Done(Err(Error::InvalidCommand(
+                InvalidCommandReason::AmbiguousCommand(candidates),
+            ))),
+            None => ExecState::Working,
+        }
     }
 
-    fn process(
+    /// Finds the literal child uniquely abbreviated by `token`. Returns `Ok(None)` when a literal
+    /// matches in full or none starts with `token`, and the candidates if several do.
+    fn abbreviate(
         &self,
-        offset: &mut usize,
-        tokens: &[String],
-        named_arguments: &mut HashMap<String, String>,
-        context: &mut ExecContext<C>,
+        token: &str,
+        matching: LiteralMatching,
+    ) -> std::result::Result<Option<usize>, Vec<String>> {
+        let literal = |index: usize| {
+            let child = &self.children[index];
+            match &child.node {
+                NodeType::Literal(name) => (name, child.matching.unwrap_or(matching)),
+                NodeType::Argument(_) => unreachable!("only literals are indexed"),
+            }
+        };
+        for &index in self.index.get(token) {
+            let (name, matching) = literal(index);
+            if matching.matches(name, token) {
+                return Ok(None);
+            }
+        }
+
+        let mut candidates: Vec<_> = match token.is_empty() {
+            true => vec![],
+            false => self
+                .index
+                .prefixed(token)
+                .filter(|index| {
+                    let (name, matching) = literal(*index);
+                    matching
+                        .normalize(name)
+                        .starts_with(matching.normalize(token).as_ref())
+                })
+                .collect(),
+        };
+        candidates.sort_unstable();
+
+        match candidates.as_slice() {
+            [] => Ok(None),
+            [index] => Ok(Some(*index)),
+            _ => Err(candidates
+                .into_iter()
+                .map(|index| literal(index).0.clone())
+                .collect()),
+        }
+    }
+
+    /// Children which may match `token`, in order: the abbreviated literal or the literals found
+    /// in the index, followed by all arguments.
+    fn candidates<'a>(
+        &'a self,
+        token: Option<&str>,
+        abbreviation: Option<usize>,
+    ) -> impl Iterator<Item = (usize, &'a Command<C, O>)> + 'a {
+        let literals = match token {
+            Some(token) => self.index.get(token),
+            None => &[],
+        };
+        abbreviation
+            .into_iter()
+            .chain(
+                literals
+                    .iter()
+                    .copied()
+                    .filter(move |index| Some(*index) != abbreviation),
+            )
+            .chain(self.index.arguments(self.children.len()))
+            .map(|index| (index, &self.children[index]))
+    }
+
+    fn process<'a>(
+        &'a self,
+        cursor: &mut Cursor,
+        segment: Segment<'a>,
+        context: &mut ExecContext<'a, C>,
+        options: MatchOptions,
     ) -> bool {
         match &self.node {
             NodeType::Literal(name) => {
-                if let Some(token) = tokens.get(*offset) {
-                    if name == token {
-                        *offset += 1;
+                if let Some(token) = segment.token(*cursor) {
+                    if self
+                        .matching
+                        .unwrap_or(options.matching)
+                        .matches(name, token.value)
+                    {
+                        *cursor = cursor.next();
+                        context.set_span(name, token.span);
                         true
                     } else {
                         false
@@ -97,17 +416,29 @@ impl<C: Debug, O> Command<C, O> {
                 }
             }
             NodeType::Argument(argument) => {
-                if let Some(named) = named_arguments.get(&argument.name) {
-                    if argument.matches(named) {
-                        context.insert_argument(argument.name.clone(), named.clone());
+                let named = segment
+                    .named
+                    .iter()
+                    .find(|(key, _)| *key == argument.name)
+                    .map(|(_, value)| value);
+                if let Some(named) = named {
+                    if argument.matches(named.value) {
+                        context.bind(&argument.name, named.value, named.span);
                         true
                     } else {
                         !argument.is_required()
                     }
-                } else if let Some(token) = tokens.get(*offset) {
-                    if argument.matches(token) {
-                        *offset += 1;
-                        context.insert_argument(argument.name.clone(), token.clone());
+                } else if let Some(token) = segment.token(*cursor) {
+                    let (value, next) = match argument.reads_input() {
+                        true => match segment.read(argument, *cursor) {
+                            Some(read) => read,
+                            None => return !argument.is_required(),
+                        },
+                        false => (token, cursor.next()),
+                    };
+                    if argument.matches(value.value) {
+                        *cursor = next;
+                        context.bind(&argument.name, value.value, value.span);
                         true
                     } else {
                         !argument.is_required()
@@ -119,6 +450,14 @@ impl<C: Debug, O> Command<C, O> {
         }
     }
 
+    /// Name of the literal or argument.
+    pub fn name(&self) -> &str {
+        match &self.node {
+            NodeType::Literal(name) => name,
+            NodeType::Argument(argument) => &argument.name,
+        }
+    }
+
     pub fn is_literal(&self) -> bool {
         matches!(self.node, NodeType::Literal(_))
     }
@@ -142,79 +481,258 @@ impl<C: Debug, O, B> Dispatcher<C, O, B> {
         command: &str,
         context: Box<dyn Fn(&B) -> C>,
     ) -> Result<Vec<O>> {
-        self.command_in_ctx(command, Some(context))
+        self.command_in_ctx(command, Some(&*context))
     }
 
-    fn command_in_ctx(
+    pub(crate) fn command_in_ctx(
         &self,
-        command: &str,
-        context: Option<Box<dyn Fn(&B) -> C>>,
+        input: &str,
+        context: Option<&dyn Fn(&B) -> C>,
     ) -> Result<Vec<O>> {
         // remove leading whitespace and prefix
-        let (command, _) = multispace0(command)?;
-        let (command, _) = tag(self.prefix.as_str())(command)?;
+        let (command, _) = multispace0(input)?;
+        let command = self.strip_prefix(command).ok_or(Error::NotACommand)?;
 
-        let (_, mut tokens) = tokenize(command)?;
-        tokens.push(Token::End);
+        let offset = input.len() - command.len();
+        let tokens: SmallVec<[Token; 16]> = Tokens::new(command, self.dialect)
+            .map(|token| token.shift(offset))
+            .collect();
+        // index of the first `;` from `from` that is not before `position`
+        let end_from = |from: usize, position: usize| {
+            tokens[from..]
+                .iter()
+                .position(|token| matches!(token, Token::End(span) if span.start >= position))
+                .map_or(tokens.len(), |index| from + index)
+        };
 
-        let mut cmd_tokens = vec![];
         let mut outputs = vec![];
-        for token in tokens {
-            if token != Token::End {
-                cmd_tokens.push(token);
-            } else if !cmd_tokens.is_empty() {
+        let mut start = 0;
+        while start < tokens.len() {
+            let mut end = end_from(start, 0);
+            while start < end {
                 let context = match &context {
                     Some(factory) => factory(&self.base_context),
                     None => (self.context_factory)(&self.base_context),
                 };
-
-                match self.execute_command(cmd_tokens, context) {
+                let overrun = Cell::new(None);
+                match self.execute_command(
+                    input,
+                    &tokens[start..end],
+                    context,
+                    Some(&overrun),
+                    false,
+                ) {
                     Ok(res) => outputs.push(res),
+                    // an argument read past `;` takes the commands it covers
+                    Err(_) if overrun.get().is_some() && end < tokens.len() => {
+                        end = end_from(end + 1, overrun.get().unwrap_or_default());
+                        continue;
+                    }
                     Err(err) => return Err(err),
                 }
-                cmd_tokens = vec![];
+                break;
             }
+            start = end + 1;
         }
         Ok(outputs)
     }
 
-    fn execute_command(&self, tokens: Vec<Token>, context: C) -> Result<O> {
-        let (named_arguments, tokens): (Vec<_>, _) = tokens
-            .into_iter()
-            .partition(|token| matches!(token, &Token::Named(_, _)));
-        let tokens = unwrap_tokens(tokens);
-        let mut named_args = map_named_arguments(named_arguments);
-
-        match self.root.execute(
-            0,
-            tokens.as_slice(),
-            &mut named_args,
-            &mut ExecContext::new(context),
+    /// Removes the longest matching prefix, static or resolved from the base context.
+    fn strip_prefix<'a>(&self, command: &'a str) -> Option<&'a str> {
+        let resolved = self
+            .prefix_resolver
+            .map(|resolver| resolver(&self.base_context))
+            .unwrap_or_default();
+        if self.prefixes.is_empty() && resolved.is_empty() {
+            return Some(command);
+        }
+
+        self.prefixes
+            .iter()
+            .chain(resolved.iter())
+            .filter(|prefix| command.starts_with(prefix.as_str()))
+            .max_by_key(|prefix| prefix.len())
+            .map(|prefix| &command[prefix.len()..])
+    }
+
+    /// Resolves and runs a command. An argument read past the tokens is recorded in `overrun`
+    /// if the command could not be resolved.
+    fn execute_command(
+        &self,
+        input: &str,
+        tokens: &[Token],
+        context: C,
+        overrun: Option<&Cell<Option<usize>>>,
+        redo: bool,
+    ) -> Result<O> {
+        let (positional, named) = split_tokens(tokens);
+        let segment = Segment {
+            input,
+            positional: &positional,
+            named: &named,
+            overrun,
+        };
+        let mut context = ExecContext::new(context);
+        let chain = match self.root.resolve(
+            Cursor::default(),
+            segment,
+            &mut context,
+            MatchOptions {
+                matching: self.matching,
+                abbreviations: self.abbreviations,
+            },
         ) {
             ExecState::Working => Err(Error::InvalidCommand(InvalidCommandReason::UnknownCommand)),
             ExecState::Done(res) => res,
+        }?;
+        if let Some(overrun) = overrun {
+            overrun.set(None);
+        }
+        let exec = chain[0]
+            .exec
+            .ok_or(Error::InvalidCommand(InvalidCommandReason::UnknownCommand))?;
+        let nodes: Chain<C, O> = chain.into_iter().rev().skip(1).collect();
+        context.set_path(nodes.iter().map(|node| node.name()));
+
+        if let Executor::Redo = exec {
+            return self.redo(context.into_context());
         }
+
+        Next::new(&self.middleware, &|context| {
+            self.check_usage(&nodes, context)?;
+            match exec {
+                Executor::Handler(handler) => {
+                    let result = handler(context);
+                    if result.is_ok() {
+                        self.start_cooldowns(&nodes, context);
+                        self.record_undo(context, input, tokens, redo);
+                    }
+                    result
+                }
+                Executor::Undo(output) => self.undo(context).map(|_| output()),
+                Executor::Redo => unreachable!("redo is handled before middleware"),
+            }
+        })
+        .run(&mut context)
     }
-}
 
-fn unwrap_tokens(tokens: Vec<Token>) -> Vec<String> {
-    let mut output = vec![];
-    for token in tokens {
-        if let Token::Simple(content) = token {
-            output.push(content);
+    fn sender(&self, context: &C) -> String {
+        self.sender
+            .map(|sender| sender(context))
+            .unwrap_or_default()
+    }
+
+    fn record_undo(
+        &self,
+        context: &mut ExecContext<C>,
+        input: &str,
+        command: &[Token],
+        redo: bool,
+    ) {
+        if let (Some(history), Some(undo)) = (&self.history, context.take_undo()) {
+            let entry = Entry {
+                input: input.to_string(),
+                tokens: command.iter().cloned().map(Token::into_owned).collect(),
+                undo,
+            };
+            history
+                .lock()
+                .unwrap()
+                .record(&self.sender(context.context()), entry, redo);
+        }
+    }
+
+    fn undo(&self, context: &mut ExecContext<C>) -> Result<()> {
+        let history = self.history.as_ref().ok_or(Error::EmptyHistory)?;
+        let sender = self.sender(context.context());
+        let entry = history.lock().unwrap().undo(&sender);
+        let entry = entry.ok_or(Error::EmptyHistory)?;
+        // a command whose undo failed is dropped rather than redone over its own effects
+        entry.undo.run(context)?;
+        history
+            .lock()
+            .unwrap()
+            .undone(&sender, entry.input, entry.tokens);
+        Ok(())
+    }
+
+    /// Replays the most recently undone command.
+    fn redo(&self, context: C) -> Result<O> {
+        let history = self.history.as_ref().ok_or(Error::EmptyHistory)?;
+        let sender = self.sender(&context);
+        let command = history.lock().unwrap().redo(&sender);
+        let (input, tokens) = command.ok_or(Error::EmptyHistory)?;
+        self.execute_command(&input, &tokens, context, None, true)
+    }
+
+    /// Applies the rate limit and cooldowns of the resolved nodes.
+    fn check_usage(&self, nodes: &[&Command<C, O>], context: &ExecContext<C>) -> Result<()> {
+        // skips resolving the sender when there is nothing to check
+        if self.rate_limit.is_none() && nodes.iter().all(|node| node.cooldown.is_none()) {
+            return Ok(());
+        }
+        let sender = self.sender(context.context());
+        let now = self.clock.now();
+        let mut usage = self.usage.lock().unwrap();
+
+        for (depth, node) in nodes.iter().enumerate() {
+            if node.cooldown.is_some() {
+                let path = context.path()[..=depth].join(" ");
+                if let Some(remaining) = usage.cooldown(&sender, &path, now) {
+                    return Err(Error::Cooldown(remaining));
+                }
+            }
+        }
+
+        if let Some(limit) = self.rate_limit {
+            if let Some(remaining) = usage.rate_limit(&sender, limit, now) {
+                return Err(Error::RateLimited(remaining));
+            }
+        }
+        Ok(())
+    }
+
+    fn start_cooldowns(&self, nodes: &[&Command<C, O>], context: &ExecContext<C>) {
+        if nodes.iter().all(|node| node.cooldown.is_none()) {
+            return;
+        }
+        let sender = self.sender(context.context());
+        let now = self.clock.now();
+        let mut usage = self.usage.lock().unwrap();
+
+        for (depth, node) in nodes.iter().enumerate() {
+            if let Some(cooldown) = node.cooldown {
+                let path = context.path()[..=depth].join(" ");
+                usage.start_cooldown(&sender, &path, now + cooldown, now);
+            }
         }
     }
-    output
 }
 
-fn map_named_arguments(tokens: Vec<Token>) -> HashMap<String, String> {
-    let mut output = HashMap::new();
+/// Splits the tokens of a command into positional and named arguments, borrowing their values.
+#[allow(clippy::type_complexity)]
+fn split_tokens<'t>(
+    tokens: &'t [Token],
+) -> (
+    SmallVec<[Spanned<&'t str>; 8]>,
+    SmallVec<[(&'t str, Spanned<&'t str>); 4]>,
+) {
+    let mut positional = SmallVec::new();
+    let mut named = SmallVec::new();
     for token in tokens {
-        if let Token::Named(key, value) = token {
-            output.insert(key, value);
+        match token {
+            Token::Simple(value) => positional.push(Spanned::new(value.value.as_ref(), value.span)),
+            Token::Named(key, value) => {
+                named.retain(|(name, _): &mut (&str, _)| *name != key.value);
+                named.push((
+                    key.value.as_ref(),
+                    Spanned::new(value.value.as_ref(), value.span),
+                ));
+            }
+            Token::End(_) => {}
         }
     }
-    output
+    (positional, named)
 }
 
 impl<C: Debug, O> From<CommandBuilder<C, O>> for Command<C, O> {
@@ -0,0 +1,34 @@
+use super::{Command, NodeType};
+use crate::Dispatcher;
+use std::fmt::Debug;
+
+impl<C: Debug, O, B> Dispatcher<C, O, B> {
+    /// Usage of every command, like `give <amount> [item]` or `mode <easy|hard>`, without
+    /// prefix.
+    pub fn usage(&self) -> Vec<String> {
+        let mut usage = vec![];
+        for child in &self.root.children {
+            child.usage(String::new(), &mut usage);
+        }
+        usage
+    }
+}
+
+impl<C: Debug, O> Command<C, O> {
+    fn usage(&self, mut path: String, usage: &mut Vec<String>) {
+        if !path.is_empty() {
+            path.push(' ');
+        }
+        match &self.node {
+            NodeType::Literal(name) => path.push_str(name),
+            NodeType::Argument(argument) => path.push_str(&argument.usage()),
+        }
+
+        if self.exec.is_some() {
+            usage.push(path.clone());
+        }
+        for child in &self.children {
+            child.usage(path.clone(), usage);
+        }
+    }
+}
@@ -1,11 +1,19 @@
+use crate::parsers::tokenize::Span;
 use crate::InvalidCommandReason::UnknownCommand;
 use nom::Err;
 use std::fmt::{Display, Formatter};
+use std::time::Duration;
 
 #[derive(Debug, Eq, PartialEq)]
 pub enum Error {
     InvalidCommand(InvalidCommandReason),
     ExecutionFailed,
+    /// The command is on cooldown for the sender, with the remaining time
+    Cooldown(Duration),
+    /// The sender ran too many commands, with the time until the next one is allowed
+    RateLimited(Duration),
+    /// There is no command to undo or redo
+    EmptyHistory,
     NotACommand,
     IncompleteBuilder,
 }
@@ -29,8 +37,15 @@ impl std::error::Error for Error {}
 #[derive(Debug, Eq, PartialEq)]
 pub enum InvalidCommandReason {
     UnknownCommand,
+    AmbiguousCommand(Vec<String>),
     MissingArgument,
     InvalidArgument,
+    /// The argument is malformed at `span`. Spans are relative to the argument when returned by a
+    /// parser and to the input when returned by [`ExecContext::get`](crate::ExecContext::get).
+    SyntaxError {
+        message: String,
+        span: Span,
+    },
     UnknownArgument,
     Unauthorised,
 }
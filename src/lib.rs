@@ -36,8 +36,11 @@ pub mod argument;
 pub mod dispatcher;
 mod error;
 pub(crate) mod parsers;
+#[cfg(feature = "repl")]
+pub mod repl;
 #[cfg(test)]
 mod tests;
 
 pub use dispatcher::*;
 pub use error::*;
+pub use parsers::tokenize::{Dialect, Span};
@@ -1,8 +1,8 @@
 use nom::branch::alt;
-use nom::bytes::complete::{is_not, take_while_m_n};
+use nom::bytes::complete::{is_not, take_till, take_while, take_while_m_n};
 use nom::character::complete::{char as nchar, multispace1};
 use nom::combinator::{map, map_opt, map_res, value, verify};
-use nom::error::{FromExternalError, ParseError};
+use nom::error::{ErrorKind, FromExternalError, ParseError};
 use nom::multi::fold_many0;
 use nom::sequence::{delimited, preceded};
 use nom::IResult;
@@ -85,3 +85,29 @@ where
 
     delimited(nchar('"'), build_string, nchar('"'))(input)
 }
+
+/// Parses a single-quoted string, taken literally without escapes.
+pub fn parse_single_quoted<'a, E: ParseError<&'a str>>(
+    input: &'a str,
+) -> IResult<&'a str, &'a str, E> {
+    delimited(nchar('\''), take_till(|c| c == '\''), nchar('\''))(input)
+}
+
+/// Parses a raw string like `r"..."` or `r#"..."#`, ending at a quote followed by as many `#`s
+/// as it started with.
+pub fn parse_raw_string<'a, E: ParseError<&'a str>>(
+    input: &'a str,
+) -> IResult<&'a str, &'a str, E> {
+    let (rest, hashes) = delimited(nchar('r'), take_while(|c| c == '#'), nchar('"'))(input)?;
+    let end = rest
+        .match_indices('"')
+        .map(|(index, _)| index)
+        .find(|index| rest[index + 1..].starts_with(hashes));
+    match end {
+        Some(end) => Ok((&rest[end + 1 + hashes.len()..], &rest[..end])),
+        None => Err(nom::Err::Error(E::from_error_kind(
+            input,
+            ErrorKind::TakeUntil,
+        ))),
+    }
+}
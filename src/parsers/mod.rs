@@ -1,2 +1,3 @@
 pub mod escaped_string;
+pub mod snbt;
 pub mod tokenize;
@@ -0,0 +1,368 @@
+use crate::parsers::tokenize::Span;
+use crate::{Error, InvalidCommandReason};
+use nom::bytes::complete::take_while1;
+use nom::character::complete::{char as nchar, multispace0};
+use nom::error::{ErrorKind, ParseError};
+use nom::IResult;
+use std::borrow::Cow;
+use std::collections::BTreeMap;
+use std::mem::discriminant;
+
+/// A value of Minecraft's NBT format, as written in SNBT.
+#[derive(Debug, Clone, PartialEq)]
+pub enum Nbt {
+    Byte(i8),
+    Short(i16),
+    Int(i32),
+    Long(i64),
+    Float(f32),
+    Double(f64),
+    ByteArray(Vec<i8>),
+    IntArray(Vec<i32>),
+    LongArray(Vec<i64>),
+    String(String),
+    /// Values of the same type
+    List(Vec<Nbt>),
+    Compound(BTreeMap<String, Nbt>),
+}
+
+impl Nbt {
+    /// Name of the type, used in error messages.
+    pub fn type_name(&self) -> &'static str {
+        match self {
+            Nbt::Byte(_) => "byte",
+            Nbt::Short(_) => "short",
+            Nbt::Int(_) => "int",
+            Nbt::Long(_) => "long",
+            Nbt::Float(_) => "float",
+            Nbt::Double(_) => "double",
+            Nbt::ByteArray(_) => "byte array",
+            Nbt::IntArray(_) => "int array",
+            Nbt::LongArray(_) => "long array",
+            Nbt::String(_) => "string",
+            Nbt::List(_) => "list",
+            Nbt::Compound(_) => "compound",
+        }
+    }
+}
+
+/// Where and why parsing failed, `length` bytes from the start of `input`.
+#[derive(Debug)]
+struct SnbtError<'a> {
+    input: &'a str,
+    length: usize,
+    message: Cow<'static, str>,
+}
+
+impl<'a> ParseError<&'a str> for SnbtError<'a> {
+    fn from_error_kind(input: &'a str, _kind: ErrorKind) -> Self {
+        Self {
+            input,
+            length: input.chars().next().map_or(0, char::len_utf8),
+            message: Cow::Borrowed("unexpected input"),
+        }
+    }
+
+    fn append(_input: &'a str, _kind: ErrorKind, other: Self) -> Self {
+        other
+    }
+}
+
+type Result<'a, T> = IResult<&'a str, T, SnbtError<'a>>;
+
+/// Maximum nesting of compounds and lists, like in Minecraft.
+const MAX_DEPTH: usize = 512;
+
+fn failure<T>(input: &str, length: usize, message: impl Into<Cow<'static, str>>) -> Result<'_, T> {
+    Err(nom::Err::Failure(SnbtError {
+        input,
+        length,
+        message: message.into(),
+    }))
+}
+
+/// Parses `input` as a single SNBT value. Errors are [`SyntaxError`]s located in `input`.
+///
+/// [`SyntaxError`]: InvalidCommandReason::SyntaxError
+pub fn parse_snbt(input: &str) -> crate::Result<Nbt> {
+    match parse_document(input) {
+        Ok((_, value)) => Ok(value),
+        Err(nom::Err::Error(error) | nom::Err::Failure(error)) => {
+            let start = input.len() - error.input.len();
+            Err(Error::InvalidCommand(InvalidCommandReason::SyntaxError {
+                message: error.message.into_owned(),
+                span: Span::new(start, start + error.length),
+            }))
+        }
+        Err(nom::Err::Incomplete(_)) => unreachable!("complete parsers do not need more input"),
+    }
+}
+
+fn parse_document(input: &str) -> Result<'_, Nbt> {
+    let (rest, value) = parse_value(input, 0)?;
+    let (rest, _) = multispace0(rest)?;
+    match rest.chars().next() {
+        None => Ok((rest, value)),
+        Some(c) => failure(
+            rest,
+            c.len_utf8(),
+            format!("unexpected `{c}` after the value"),
+        ),
+    }
+}
+
+/// Parses a value nested in `depth` compounds and lists.
+fn parse_value(input: &str, depth: usize) -> Result<'_, Nbt> {
+    let (input, _) = multispace0(input)?;
+    match input.chars().next() {
+        Some('{' | '[') if depth == MAX_DEPTH => failure(
+            input,
+            1,
+            format!("too deeply nested, expected at most {MAX_DEPTH} levels"),
+        ),
+        Some('{') => parse_compound(input, depth + 1),
+        Some('[') => parse_list(input, depth + 1),
+        Some('"' | '\'') => parse_quoted(input).map(|(rest, value)| (rest, Nbt::String(value))),
+        Some(_) => parse_scalar(input),
+        None => failure(input, 0, "expected a value"),
+    }
+}
+
+fn is_unquoted(c: char) -> bool {
+    c.is_ascii_alphanumeric() || matches!(c, '_' | '-' | '.' | '+')
+}
+
+fn parse_unquoted(input: &str) -> Result<'_, &str> {
+    take_while1(is_unquoted)(input)
+}
+
+/// Parses a string in double or single quotes, where `\` escapes the quote and itself.
+fn parse_quoted(input: &str) -> Result<'_, String> {
+    let (rest, quote) = nom::branch::alt((nchar('"'), nchar('\'')))(input)?;
+    let mut value = String::new();
+    let mut chars = rest.char_indices();
+    while let Some((index, c)) = chars.next() {
+        match c {
+            '\\' => match chars.next() {
+                Some((_, escaped)) if escaped == quote || escaped == '\\' => value.push(escaped),
+                Some((_, escaped)) => {
+                    return failure(
+                        &rest[index..],
+                        1 + escaped.len_utf8(),
+                        format!("invalid escape `\\{escaped}`"),
+                    )
+                }
+                None => break,
+            },
+            c if c == quote => return Ok((&rest[index + 1..], value)),
+            c => value.push(c),
+        }
+    }
+    failure(input, input.len(), "unclosed string")
+}
+
+fn parse_scalar(input: &str) -> Result<'_, Nbt> {
+    let Ok((rest, word)) = parse_unquoted(input) else {
+        let c = input.chars().next().unwrap_or_default();
+        return failure(input, c.len_utf8(), format!("unexpected `{c}`"));
+    };
+    match parse_number(word) {
+        Some(Ok(value)) => Ok((rest, value)),
+        Some(Err(message)) => failure(input, word.len(), message),
+        None => Ok((rest, Nbt::String(word.to_string()))),
+    }
+}
+
+/// Parses numbers and booleans. Returns `None` for words which are strings, and an error for
+/// numbers out of range.
+fn parse_number(word: &str) -> Option<std::result::Result<Nbt, String>> {
+    match word {
+        "true" => return Some(Ok(Nbt::Byte(1))),
+        "false" => return Some(Ok(Nbt::Byte(0))),
+        _ => {}
+    }
+    let out_of_range = |type_name: &str| format!("`{word}` is out of range for a {type_name}");
+
+    let (body, suffix) = match word.char_indices().next_back() {
+        Some((index, suffix)) if suffix.is_ascii_alphabetic() && index > 0 => {
+            (&word[..index], Some(suffix.to_ascii_lowercase()))
+        }
+        _ => (word, None),
+    };
+    let integer = {
+        let digits = body.strip_prefix(['-', '+']).unwrap_or(body);
+        !digits.is_empty() && digits.chars().all(|c| c.is_ascii_digit())
+    };
+    let decimal = body.contains(|c: char| c.is_ascii_digit())
+        && body
+            .chars()
+            .all(|c| c.is_ascii_digit() || matches!(c, '.' | 'e' | 'E' | '+' | '-'))
+        && body.parse::<f64>().is_ok();
+
+    let double = || {
+        body.parse()
+            .ok()
+            .filter(|value: &f64| value.is_finite())
+            .map(Nbt::Double)
+            .ok_or_else(|| out_of_range("double"))
+    };
+
+    match suffix {
+        Some('b') if integer => Some(
+            body.parse()
+                .map(Nbt::Byte)
+                .map_err(|_| out_of_range("byte")),
+        ),
+        Some('s') if integer => Some(
+            body.parse()
+                .map(Nbt::Short)
+                .map_err(|_| out_of_range("short")),
+        ),
+        Some('l') if integer => Some(
+            body.parse()
+                .map(Nbt::Long)
+                .map_err(|_| out_of_range("long")),
+        ),
+        Some('f') if decimal => Some(
+            body.parse()
+                .ok()
+                .filter(|value: &f32| value.is_finite())
+                .map(Nbt::Float)
+                .ok_or_else(|| out_of_range("float")),
+        ),
+        Some('d') if decimal => Some(double()),
+        None if integer => Some(
+            body.parse()
+                .map(Nbt::Int)
+                .map_err(|_| format!("`{word}` is out of range for an int, add `L` for a long")),
+        ),
+        None if decimal && body.contains(['.', 'e', 'E']) => Some(double()),
+        _ => None,
+    }
+}
+
+fn parse_compound(input: &str, depth: usize) -> Result<'_, Nbt> {
+    let (mut input, _) = nchar('{')(input)?;
+    let mut compound = BTreeMap::new();
+    let (rest, _) = multispace0(input)?;
+    if let Some(rest) = rest.strip_prefix('}') {
+        return Ok((rest, Nbt::Compound(compound)));
+    }
+
+    loop {
+        let (rest, _) = multispace0(input)?;
+        let (rest, key) = match rest.chars().next() {
+            Some('"' | '\'') => parse_quoted(rest)?,
+            _ => match parse_unquoted(rest) {
+                Ok((rest, key)) => (rest, key.to_string()),
+                Err(_) => return failure(rest, first_char(rest), "expected a key"),
+            },
+        };
+        let (rest, _) = multispace0(rest)?;
+        let (rest, ()) = expect(rest, ':')?;
+        let (rest, value) = parse_value(rest, depth)?;
+        compound.insert(key, value);
+
+        let (rest, _) = multispace0(rest)?;
+        match rest.chars().next() {
+            Some(',') => input = &rest[1..],
+            Some('}') => return Ok((&rest[1..], Nbt::Compound(compound))),
+            _ => return failure(rest, first_char(rest), "expected `,` or `}`"),
+        }
+    }
+}
+
+/// Parses a list, or an array like `[B;1b,2b]`.
+fn parse_list(input: &str, depth: usize) -> Result<'_, Nbt> {
+    let (input, _) = nchar('[')(input)?;
+    let (after_type, _) = multispace0(input)?;
+    let array = match after_type.as_bytes() {
+        [kind @ (b'B' | b'I' | b'L'), rest @ ..] if rest.trim_ascii_start().starts_with(b";") => {
+            Some(*kind)
+        }
+        _ => None,
+    };
+    let input = match array {
+        Some(_) => &after_type[after_type.find(';').map_or(0, |index| index + 1)..],
+        None => input,
+    };
+
+    let mut elements = vec![];
+    let (mut rest, _) = multispace0(input)?;
+    if let Some(after) = rest.strip_prefix(']') {
+        rest = after;
+    } else {
+        loop {
+            let (start, _) = multispace0(rest)?;
+            let (after, element) = parse_value(start, depth)?;
+            let length = start.len() - after.len();
+            let expected = match array {
+                Some(b'B') => Some(Nbt::Byte(0)),
+                Some(b'I') => Some(Nbt::Int(0)),
+                Some(b'L') => Some(Nbt::Long(0)),
+                _ => elements.first().cloned(),
+            };
+            if let Some(expected) = expected {
+                if discriminant(&expected) != discriminant(&element) {
+                    let container = match array {
+                        Some(_) => "array",
+                        None => "list",
+                    };
+                    return failure(
+                        start,
+                        length,
+                        format!(
+                            "expected {} in the {container}, found {}",
+                            expected.type_name(),
+                            element.type_name()
+                        ),
+                    );
+                }
+            }
+            elements.push(element);
+
+            let (after, _) = multispace0(after)?;
+            match after.chars().next() {
+                Some(',') => rest = &after[1..],
+                Some(']') => {
+                    rest = &after[1..];
+                    break;
+                }
+                _ => return failure(after, first_char(after), "expected `,` or `]`"),
+            }
+        }
+    }
+
+    let value = match array {
+        Some(b'B') => Nbt::ByteArray(unwrap_all(elements, |element| match element {
+            Nbt::Byte(value) => Some(value),
+            _ => None,
+        })),
+        Some(b'I') => Nbt::IntArray(unwrap_all(elements, |element| match element {
+            Nbt::Int(value) => Some(value),
+            _ => None,
+        })),
+        Some(b'L') => Nbt::LongArray(unwrap_all(elements, |element| match element {
+            Nbt::Long(value) => Some(value),
+            _ => None,
+        })),
+        _ => Nbt::List(elements),
+    };
+    Ok((rest, value))
+}
+
+/// Unwraps array elements, whose type was checked while parsing.
+fn unwrap_all<T>(elements: Vec<Nbt>, unwrap: fn(Nbt) -> Option<T>) -> Vec<T> {
+    elements.into_iter().filter_map(unwrap).collect()
+}
+
+fn expect(input: &str, expected: char) -> Result<'_, ()> {
+    match input.strip_prefix(expected) {
+        Some(rest) => Ok((rest, ())),
+        None => failure(input, first_char(input), format!("expected `{expected}`")),
+    }
+}
+
+fn first_char(input: &str) -> usize {
+    input.chars().next().map_or(0, char::len_utf8)
+}
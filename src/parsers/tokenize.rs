@@ -1,58 +1,317 @@
-use crate::parsers::escaped_string::parse_string;
+use crate::parsers::escaped_string::{parse_raw_string, parse_single_quoted, parse_string};
 use crate::Result;
 use nom::branch::alt;
-use nom::bytes::complete::{tag, take_till1};
-use nom::character::complete::{alphanumeric1, multispace0};
-use nom::combinator::map;
-use nom::sequence::separated_pair;
+use nom::bytes::complete::{tag, take_while};
+use nom::character::complete::{multispace0, satisfy};
+use nom::combinator::recognize;
+use nom::error::{Error, ErrorKind};
+use nom::sequence::{pair, terminated};
 use nom::IResult;
+use std::borrow::Cow;
+use std::ops::Range;
 
-pub fn tokenize(data: &str) -> Result<(&str, Vec<Token>)> {
-    let mut output = Vec::<Token>::new();
+/// String syntax accepted by the tokenizer, in addition to double-quoted strings with escapes.
+#[derive(Debug, Clone, Copy, Default, Eq, PartialEq)]
+pub struct Dialect {
+    /// Single-quoted strings without escapes, `'C:\temp'`
+    pub single_quotes: bool,
+    /// Raw strings, `r"..."` and `r#"..."#`
+    pub raw_strings: bool,
+    /// `\;` outside of quotes is a literal `;` instead of ending the command
+    pub escaped_semicolons: bool,
+}
+
+impl Dialect {
+    /// Enables all optional syntax.
+    pub fn extended() -> Self {
+        Self {
+            single_quotes: true,
+            raw_strings: true,
+            escaped_semicolons: true,
+        }
+    }
+}
+
+pub fn tokenize(input: &str, dialect: Dialect) -> Result<(&str, Vec<Token<'_>>)> {
+    let mut tokens = Tokens::new(input, dialect);
+    let output = tokens.by_ref().collect();
+    Ok((tokens.remainder(), output))
+}
+
+/// Lazily reads the tokens of an input, borrowing from it.
+pub struct Tokens<'a> {
+    input: &'a str,
+    data: &'a str,
+    dialect: Dialect,
+}
+
+impl<'a> Tokens<'a> {
+    pub fn new(input: &'a str, dialect: Dialect) -> Self {
+        Self {
+            input,
+            data: input,
+            dialect,
+        }
+    }
+
+    /// The input left after the last token read.
+    pub fn remainder(&self) -> &'a str {
+        self.data
+    }
+}
+
+impl<'a> Iterator for Tokens<'a> {
+    type Item = Token<'a>;
+
+    fn next(&mut self) -> Option<Self::Item> {
+        let (remainder, token) = read_token(self.input, self.data, self.dialect).ok()?;
+        self.data = remainder;
+        Some(token)
+    }
+}
 
-    let mut data = data;
+/// Whether `data` ends inside a quoted string, so more input is needed to complete it.
+pub fn is_incomplete(data: &str, dialect: Dialect) -> bool {
+    let mut chars = data.char_indices().peekable();
+    let mut previous = None;
+    while let Some((index, c)) = chars.next() {
+        if previous.is_none_or(|p: char| p.is_whitespace() || p == ';' || p == '=') {
+            let rest = &data[index..];
+            let closed = if c == '"' {
+                parse_string::<Error<&str>>(rest)
+                    .ok()
+                    .map(|(remainder, _)| remainder)
+            } else if c == '\'' && dialect.single_quotes {
+                parse_single_quoted::<Error<&str>>(rest)
+                    .ok()
+                    .map(|(remainder, _)| remainder)
+            } else if c == 'r'
+                && dialect.raw_strings
+                && rest[1..].trim_start_matches('#').starts_with('"')
+            {
+                parse_raw_string::<Error<&str>>(rest)
+                    .ok()
+                    .map(|(remainder, _)| remainder)
+            } else {
+                previous = Some(c);
+                continue;
+            };
 
-    while let Ok((remainder, token)) = read_token(data) {
-        data = remainder;
-        output.push(token);
+            match closed {
+                Some(remainder) => {
+                    let end = data.len() - remainder.len();
+                    while chars.next_if(|(index, _)| *index < end).is_some() {}
+                    previous = data[..end].chars().next_back();
+                    continue;
+                }
+                // an invalid escape sequence is not fixed by more input
+                None if c == '"' => return !has_closing_quote(&rest[1..]),
+                None => return true,
+            }
+        }
+        previous = Some(c);
     }
-    Ok((data, output))
+    false
 }
 
-fn read_token(data: &str) -> Result<(&str, Token)> {
+fn has_closing_quote(data: &str) -> bool {
+    let mut escaped = false;
+    for c in data.chars() {
+        match c {
+            _ if escaped => escaped = false,
+            '\\' => escaped = true,
+            '"' => return true,
+            _ => {}
+        }
+    }
+    false
+}
+
+/// Reads the next token of `data`, a suffix of `input` which spans are relative to.
+fn read_token<'a>(input: &str, data: &'a str, dialect: Dialect) -> Result<(&'a str, Token<'a>)> {
+    let span = |from: &str, to: &str| Span::new(input.len() - from.len(), input.len() - to.len());
+
     // remove leading whitespace
     let (data, _) = multispace0(data)?;
     // check command end (;)
-    let result: IResult<&str, &str, nom::error::Error<&str>> = tag(";")(data);
+    let result: IResult<&str, &str, Error<&str>> = tag(";")(data);
     if let Ok((remainder, _)) = result {
-        return Ok((remainder, Token::End));
+        return Ok((remainder, Token::End(span(data, remainder))));
     }
 
-    let (remainder, token) = alt((
-        map(
-            separated_pair(
-                map(alphanumeric1, |s: &str| s.to_string()),
-                tag("="),
-                alt((parse_string, map(alphanumeric1, |s: &str| s.to_string()))),
-            ),
-            |(key, value)| Token::Named(key, value),
-        ),
-        map(parse_string, Token::Simple),
-        map(
-            take_till1(|c: char| c.is_whitespace() || c.eq(&';')),
-            |s: &str| Token::Simple(s.to_string()),
-        ),
+    let named: IResult<&str, &str> = terminated(read_key, tag("="))(data);
+    if let Ok((value_data, key)) = named {
+        let value: IResult<&str, Cow<str>> = alt((
+            |data| read_quoted(data, dialect),
+            |data| read_bare(data, dialect),
+        ))(value_data);
+        if let Ok((remainder, value)) = value {
+            let key = Spanned::new(Cow::Borrowed(key), span(data, &data[key.len()..]));
+            let value = Spanned::new(value, span(value_data, remainder));
+            return Ok((remainder, Token::Named(key, value)));
+        }
+    }
+
+    let (remainder, value) = alt((
+        |data| read_quoted(data, dialect),
+        |data| read_bare(data, dialect),
     ))(data)?;
 
-    Ok((remainder, token))
+    Ok((
+        remainder,
+        Token::Simple(Spanned::new(value, span(data, remainder))),
+    ))
+}
+
+/// Reads the key of a named argument, an identifier which may contain `-` and `_`.
+fn read_key(data: &str) -> IResult<&str, &str> {
+    recognize(pair(
+        satisfy(|c| c.is_alphabetic() || c == '_'),
+        take_while(|c: char| c.is_alphanumeric() || c == '_' || c == '-'),
+    ))(data)
+}
+
+/// Reads a quoted string, borrowing it from `data` unless it contains escapes.
+fn read_quoted(data: &str, dialect: Dialect) -> IResult<&str, Cow<'_, str>> {
+    if dialect.raw_strings {
+        if let Ok((remainder, value)) = parse_raw_string::<Error<&str>>(data) {
+            return Ok((remainder, Cow::Borrowed(value)));
+        }
+    }
+    if dialect.single_quotes {
+        if let Ok((remainder, value)) = parse_single_quoted::<Error<&str>>(data) {
+            return Ok((remainder, Cow::Borrowed(value)));
+        }
+    }
+    if let Some(content) = data.strip_prefix('"') {
+        if let Some(end) = content.find(['"', '\\']) {
+            if content[end..].starts_with('"') {
+                return Ok((&content[end + 1..], Cow::Borrowed(&content[..end])));
+            }
+        }
+    }
+    let (remainder, value) = parse_string(data)?;
+    Ok((remainder, Cow::Owned(value)))
+}
+
+/// Reads an unquoted token up to whitespace or the end of the command.
+fn read_bare(data: &str, dialect: Dialect) -> IResult<&str, Cow<'_, str>> {
+    let mut output = Cow::Borrowed("");
+    let mut start = 0;
+    let mut chars = data.char_indices().peekable();
+    while let Some((index, c)) = chars.next() {
+        if dialect.escaped_semicolons && c == '\\' && chars.next_if(|(_, c)| *c == ';').is_some() {
+            let owned = output.to_mut();
+            owned.push_str(&data[start..index]);
+            owned.push(';');
+            start = index + 2;
+        } else if c.is_whitespace() || c == ';' {
+            return bare_token(data, start, index, output);
+        }
+    }
+    bare_token(data, start, data.len(), output)
+}
+
+/// Ends a bare token at `end`, where `output` holds any unescaped text before `start`.
+fn bare_token<'a>(
+    data: &'a str,
+    start: usize,
+    end: usize,
+    mut output: Cow<'a, str>,
+) -> IResult<&'a str, Cow<'a, str>> {
+    match &mut output {
+        Cow::Borrowed(_) => output = Cow::Borrowed(&data[start..end]),
+        Cow::Owned(owned) => owned.push_str(&data[start..end]),
+    }
+    if output.is_empty() {
+        Err(nom::Err::Error(Error::new(data, ErrorKind::TakeTill1)))
+    } else {
+        Ok((&data[end..], output))
+    }
+}
+
+/// Byte range of a token in the input.
+#[derive(Debug, Clone, Copy, Default, Eq, PartialEq, Hash)]
+pub struct Span {
+    pub start: usize,
+    pub end: usize,
+}
+
+impl Span {
+    pub fn new(start: usize, end: usize) -> Self {
+        Self { start, end }
+    }
+
+    pub fn len(&self) -> usize {
+        self.end - self.start
+    }
+
+    pub fn is_empty(&self) -> bool {
+        self.start == self.end
+    }
+
+    pub fn shift(self, offset: usize) -> Self {
+        Self::new(self.start + offset, self.end + offset)
+    }
+
+    /// Converts the byte range to a range of chars in `input`.
+    pub fn char_range(&self, input: &str) -> Range<usize> {
+        let start = input[..self.start].chars().count();
+        start..start + input[self.start..self.end].chars().count()
+    }
+
+    /// The spanned text in `input`.
+    pub fn slice<'a>(&self, input: &'a str) -> &'a str {
+        &input[self.start..self.end]
+    }
+}
+
+#[derive(Debug, Clone, Eq, PartialEq)]
+pub struct Spanned<T> {
+    pub value: T,
+    pub span: Span,
+}
+
+impl<T> Spanned<T> {
+    pub fn new(value: T, span: Span) -> Self {
+        Self { value, span }
+    }
 }
 
 #[derive(Debug, Clone, Eq, PartialEq)]
-pub enum Token {
+pub enum Token<'a> {
     /// Representing a command, subcommand or positional argument
-    Simple(String),
-    /// Representing an named argument
-    Named(String, String),
+    Simple(Spanned<Cow<'a, str>>),
+    /// Representing an named argument, key and value
+    Named(Spanned<Cow<'a, str>>, Spanned<Cow<'a, str>>),
     /// Representing end of command
-    End,
+    End(Span),
+}
+
+impl Token<'_> {
+    /// Moves the spans of the token by `offset` bytes.
+    pub fn shift(self, offset: usize) -> Self {
+        match self {
+            Token::Simple(value) => {
+                Token::Simple(Spanned::new(value.value, value.span.shift(offset)))
+            }
+            Token::Named(key, value) => Token::Named(
+                Spanned::new(key.value, key.span.shift(offset)),
+                Spanned::new(value.value, value.span.shift(offset)),
+            ),
+            Token::End(span) => Token::End(span.shift(offset)),
+        }
+    }
+
+    /// Copies the borrowed parts of the token, so it can outlive the input.
+    pub fn into_owned(self) -> Token<'static> {
+        let owned = |spanned: Spanned<Cow<str>>| {
+            Spanned::new(Cow::Owned(spanned.value.into_owned()), spanned.span)
+        };
+        match self {
+            Token::Simple(value) => Token::Simple(owned(value)),
+            Token::Named(key, value) => Token::Named(owned(key), owned(value)),
+            Token::End(span) => Token::End(span),
+        }
+    }
 }
@@ -0,0 +1,152 @@
+//! Interactive shell around a [`Dispatcher`], enabled by the `repl` feature.
+//!
+//! ```no_run
+//! use yogurt::repl::Repl;
+//! use yogurt::{Command, Dispatcher};
+//!
+//! let dispatcher = Dispatcher::builder()
+//!     .base_context(())
+//!     .context_factory(|_| ())
+//!     .child(Command::literal("ping").exec(|_| Ok("pong")))
+//!     .build()
+//!     .unwrap();
+//!
+//! Repl::new(&dispatcher)
+//!     .history_file(".history")
+//!     .output(|output| println!("{output}"))
+//!     .run()
+//!     .unwrap();
+//! ```
+
+use crate::Dispatcher;
+use owo_colors::OwoColorize;
+use rustyline::completion::Completer;
+use rustyline::error::ReadlineError;
+use rustyline::highlight::Highlighter;
+use rustyline::hint::Hinter;
+use rustyline::history::DefaultHistory;
+use rustyline::validate::{ValidationContext, ValidationResult, Validator};
+use rustyline::{Context, Editor, Helper};
+use std::fmt::Debug;
+use std::path::PathBuf;
+
+/// Creates the context of a command from the base context.
+type ContextFactory<'a, B, C> = Box<dyn Fn(&B) -> C + 'a>;
+
+pub struct Repl<'a, C: Debug, O, B> {
+    dispatcher: &'a Dispatcher<C, O, B>,
+    prompt: String,
+    history_file: Option<PathBuf>,
+    output: Box<dyn FnMut(O) + 'a>,
+    context: Option<ContextFactory<'a, B, C>>,
+}
+
+impl<'a, C: Debug, O, B> Repl<'a, C, O, B> {
+    pub fn new(dispatcher: &'a Dispatcher<C, O, B>) -> Self {
+        Self {
+            dispatcher,
+            prompt: "> ".to_string(),
+            history_file: None,
+            output: Box::new(|_| {}),
+            context: None,
+        }
+    }
+
+    pub fn prompt(mut self, prompt: impl Into<String>) -> Self {
+        self.prompt = prompt.into();
+        self
+    }
+
+    /// Loads history from and saves it to `path`.
+    pub fn history_file(mut self, path: impl Into<PathBuf>) -> Self {
+        self.history_file = Some(path.into());
+        self
+    }
+
+    /// Handles the outputs of executed commands, which are discarded by default.
+    pub fn output(mut self, output: impl FnMut(O) + 'a) -> Self {
+        self.output = Box::new(output);
+        self
+    }
+
+    /// Creates the context of each command with `factory` instead of the dispatcher's context
+    /// factory, like [`Dispatcher::run_command_in_context`].
+    pub fn context(mut self, factory: impl Fn(&B) -> C + 'a) -> Self {
+        self.context = Some(Box::new(factory));
+        self
+    }
+
+    /// Reads and runs commands until end of input.
+    pub fn run(mut self) -> rustyline::Result<()> {
+        let mut editor = Editor::<ReplHelper<C, O, B>, DefaultHistory>::new()?;
+        editor.set_helper(Some(ReplHelper {
+            dispatcher: self.dispatcher,
+            context: self.context.as_deref(),
+        }));
+        if let Some(path) = &self.history_file {
+            // the file does not exist on first run
+            let _ = editor.load_history(path);
+        }
+
+        loop {
+            let line = match editor.readline(&self.prompt) {
+                Ok(line) => line,
+                Err(ReadlineError::Interrupted) => continue,
+                Err(ReadlineError::Eof) => return Ok(()),
+                Err(err) => return Err(err),
+            };
+            if line.trim().is_empty() {
+                continue;
+            }
+
+            editor.add_history_entry(line.as_str())?;
+            if let Some(path) = &self.history_file {
+                editor.save_history(path)?;
+            }
+            match self
+                .dispatcher
+                .command_in_ctx(&line, self.context.as_deref())
+            {
+                Ok(outputs) => outputs.into_iter().for_each(&mut self.output),
+                Err(err) => eprintln!("{}", format!("error: {err}").red()),
+            }
+        }
+    }
+}
+
+struct ReplHelper<'a, C: Debug, O, B> {
+    dispatcher: &'a Dispatcher<C, O, B>,
+    context: Option<&'a (dyn Fn(&B) -> C + 'a)>,
+}
+
+impl<C: Debug, O, B> Completer for ReplHelper<'_, C, O, B> {
+    type Candidate = String;
+
+    fn complete(
+        &self,
+        line: &str,
+        pos: usize,
+        _ctx: &Context<'_>,
+    ) -> rustyline::Result<(usize, Vec<String>)> {
+        let completions = self.dispatcher.complete_in_ctx(&line[..pos], self.context);
+        Ok((completions.start, completions.candidates))
+    }
+}
+
+impl<C: Debug, O, B> Validator for ReplHelper<'_, C, O, B> {
+    fn validate(&self, ctx: &mut ValidationContext) -> rustyline::Result<ValidationResult> {
+        Ok(if self.dispatcher.is_incomplete(ctx.input()) {
+            ValidationResult::Incomplete
+        } else {
+            ValidationResult::Valid(None)
+        })
+    }
+}
+
+impl<C: Debug, O, B> Hinter for ReplHelper<'_, C, O, B> {
+    type Hint = String;
+}
+
+impl<C: Debug, O, B> Highlighter for ReplHelper<'_, C, O, B> {}
+
+impl<C: Debug, O, B> Helper for ReplHelper<'_, C, O, B> {}
@@ -1,7 +1,29 @@
-use crate::argument::parser::{ArgumentParser, IntArgument, StringArgument};
+use crate::argument::collection::{ListArgument, MapArgument};
+use crate::argument::coordinates::{
+    Coordinate, Origin, Rotation, RotationArgument, Vec2Argument, Vec3, Vec3Argument,
+};
+use crate::argument::parser::{
+    ArgumentEnum, ArgumentParser, BoolArgument, BoundedIntArgument, ChoiceArgument,
+    DurationArgument, EnumArgument, F64Argument, FromStrArgument, IntArgument, IpAddrArgument,
+    IpNetArgument, SocketAddrArgument, StringArgument, U8Argument, Url, UrlArgument,
+};
+use crate::argument::path::{PathArgument, PathKind};
+use crate::argument::reader::StringReader;
+use crate::argument::resource::{ResourceId, ResourceIdArgument};
+use crate::argument::snbt::{Nbt, SnbtArgument};
+use crate::argument::Argument;
 use crate::parsers::escaped_string::parse_string;
-use crate::parsers::tokenize::{tokenize, Token};
-use crate::{Command, Dispatcher, Error, InvalidCommandReason};
+use crate::parsers::tokenize::{is_incomplete, tokenize, Spanned, Token};
+use crate::{
+    Command, Completions, Dialect, Dispatcher, Error, ExecContext, Highlight, HighlightKind,
+    InvalidCommandReason, LiteralMatching, ManualClock, Next, Span,
+};
+use std::alloc::{GlobalAlloc, Layout, System};
+use std::borrow::Cow;
+use std::cell::Cell;
+use std::net::{IpAddr, Ipv6Addr, SocketAddr};
+use std::sync::{Arc, Mutex};
+use std::time::Duration;
 
 #[test]
 fn command() {
@@ -87,15 +109,19 @@ fn unclosed_quoted_string() {
 #[test]
 fn test_tokenize() {
     let sample = r#"test "epic parameter"; bye=2 ; hello="good day"   "#;
+    let spanned = |value, start, end| Spanned::new(Cow::Borrowed(value), Span::new(start, end));
     let parsed_sample = vec![
-        Token::Simple("test".to_string()),
-        Token::Simple("epic parameter".to_string()),
-        Token::End,
-        Token::Named("bye".to_string(), "2".to_string()),
-        Token::End,
-        Token::Named("hello".to_string(), "good day".to_string()),
+        Token::Simple(spanned("test", 0, 4)),
+        Token::Simple(spanned("epic parameter", 5, 21)),
+        Token::End(Span::new(21, 22)),
+        Token::Named(spanned("bye", 23, 26), spanned("2", 27, 28)),
+        Token::End(Span::new(29, 30)),
+        Token::Named(spanned("hello", 31, 36), spanned("good day", 37, 47)),
     ];
-    assert_eq!(tokenize(sample), Ok(("   ", parsed_sample)));
+    assert_eq!(
+        tokenize(sample, Dialect::default()),
+        Ok(("   ", parsed_sample))
+    );
 }
 
 #[test]
@@ -111,3 +137,2132 @@ fn int_argument() {
         Err(Error::InvalidCommand(InvalidCommandReason::InvalidArgument))
     )
 }
+
+#[test]
+fn literal_matching() {
+    let dispatcher = Dispatcher::builder()
+        .base_context(())
+        .context_factory(|_| ())
+        .matching(LiteralMatching::AsciiCaseInsensitive)
+        .child(Command::literal("ping").exec(|_| Ok("ping")))
+        .child(
+            Command::literal("pong")
+                .matching(LiteralMatching::CaseSensitive)
+                .exec(|_| Ok("pong")),
+        )
+        .child(
+            Command::literal("stra\u{df}e")
+                .matching(LiteralMatching::UnicodeCaseFold)
+                .exec(|_| Ok("strasse")),
+        )
+        .build()
+        .unwrap();
+
+    assert_eq!(dispatcher.run_command("PiNg"), Ok(vec!["ping"]));
+    assert_eq!(dispatcher.run_command("pong"), Ok(vec!["pong"]));
+    assert_eq!(
+        dispatcher.run_command("PONG"),
+        Err(Error::InvalidCommand(InvalidCommandReason::UnknownCommand))
+    );
+    assert_eq!(dispatcher.run_command("STRASSE"), Ok(vec!["strasse"]));
+}
+
+#[test]
+fn unicode_normalisation() {
+    let nfc = "caf\u{e9}";
+    let nfd = "cafe\u{301}";
+    assert!(!LiteralMatching::CaseSensitive.matches(nfc, nfd));
+    assert!(LiteralMatching::UnicodeCaseFold.matches(nfc, nfd));
+    assert!(LiteralMatching::UnicodeCaseFold.matches(nfc, "CAF\u{c9}"));
+    assert_eq!(
+        LiteralMatching::UnicodeCaseFold.normalize(nfc),
+        LiteralMatching::UnicodeCaseFold.normalize(nfd)
+    );
+}
+
+#[test]
+fn abbreviations() {
+    let dispatcher = Dispatcher::builder()
+        .base_context(())
+        .context_factory(|_| ())
+        .abbreviations(true)
+        .child(
+            Command::literal("show")
+                .child(
+                    Command::literal("interfaces")
+                        .exec(|_| Ok("interfaces"))
+                        .child(Command::literal("brief").exec(|_| Ok("brief"))),
+                )
+                .child(Command::literal("int").exec(|_| Ok("int"))),
+        )
+        .child(Command::literal("shutdown").exec(|_| Ok("shutdown")))
+        .child(Command::literal("set").exec(|_| Ok("set")))
+        .build()
+        .unwrap();
+
+    assert_eq!(
+        dispatcher.run_command("show interfaces brief"),
+        Ok(vec!["brief"])
+    );
+    assert_eq!(dispatcher.run_command("sho inte br"), Ok(vec!["brief"]));
+    // exact match wins over a longer sibling
+    assert_eq!(dispatcher.run_command("show int"), Ok(vec!["int"]));
+    assert_eq!(dispatcher.run_command("se"), Ok(vec!["set"]));
+    assert_eq!(
+        dispatcher.run_command("sh int br"),
+        Err(Error::InvalidCommand(
+            InvalidCommandReason::AmbiguousCommand(vec![
+                "show".to_string(),
+                "shutdown".to_string()
+            ])
+        ))
+    );
+}
+
+#[test]
+fn abbreviations_disabled() {
+    let dispatcher = Dispatcher::builder()
+        .base_context(())
+        .context_factory(|_| ())
+        .child(Command::literal("show").exec(|_| Ok(())))
+        .build()
+        .unwrap();
+
+    assert_eq!(
+        dispatcher.run_command("sh"),
+        Err(Error::InvalidCommand(InvalidCommandReason::UnknownCommand))
+    );
+}
+
+#[test]
+fn multiple_prefixes() {
+    let dispatcher = Dispatcher::builder()
+        .prefix("!")
+        .prefixes(["/", "<@123>"])
+        .base_context(())
+        .context_factory(|_| ())
+        .child(Command::literal("ping").exec(|_| Ok("pong")))
+        .build()
+        .unwrap();
+
+    assert_eq!(dispatcher.run_command("!ping"), Ok(vec!["pong"]));
+    assert_eq!(dispatcher.run_command("/ping"), Ok(vec!["pong"]));
+    assert_eq!(dispatcher.run_command("<@123> ping"), Ok(vec!["pong"]));
+    assert_eq!(dispatcher.run_command("ping"), Err(Error::NotACommand));
+    assert_eq!(
+        dispatcher.run_command("<@456> ping"),
+        Err(Error::NotACommand)
+    );
+}
+
+#[test]
+fn resolved_prefixes() {
+    let dispatcher = Dispatcher::builder()
+        .prefix("/")
+        .prefix_resolver(|guild_prefix: &&str| vec![guild_prefix.to_string()])
+        .base_context("$$")
+        .context_factory(|_| ())
+        .child(Command::literal("ping").exec(|_| Ok("pong")))
+        .build()
+        .unwrap();
+
+    assert_eq!(dispatcher.run_command("$$ping"), Ok(vec!["pong"]));
+    assert_eq!(dispatcher.run_command("/ping"), Ok(vec!["pong"]));
+    assert_eq!(dispatcher.run_command("$ping"), Err(Error::NotACommand));
+}
+
+#[test]
+fn cooldown() {
+    let clock = ManualClock::new();
+    let dispatcher = Dispatcher::builder()
+        .base_context(())
+        .context_factory(|_| ())
+        .clock(clock.clone())
+        .child(
+            Command::literal("heal")
+                .cooldown(Duration::from_secs(10))
+                .exec(|_| Ok(()))
+                .child(Command::argument("amount", IntArgument, true).exec(|_| Ok(()))),
+        )
+        .child(Command::literal("ping").exec(|_| Ok(())))
+        .build()
+        .unwrap();
+
+    dispatcher.run_command("heal 5").unwrap();
+    clock.advance(Duration::from_secs(4));
+    assert_eq!(
+        dispatcher.run_command("heal"),
+        Err(Error::Cooldown(Duration::from_secs(6)))
+    );
+    dispatcher.run_command("ping").unwrap();
+    clock.advance(Duration::from_secs(6));
+    dispatcher.run_command("heal").unwrap();
+}
+
+#[test]
+fn cooldown_per_sender() {
+    let clock = ManualClock::new();
+    let dispatcher = Dispatcher::builder()
+        .base_context(())
+        .context_factory(|_| String::new())
+        .sender(|sender: &String| sender.clone())
+        .clock(clock.clone())
+        .child(
+            Command::literal("heal")
+                .cooldown(Duration::from_secs(10))
+                .exec(|_| Ok(())),
+        )
+        .build()
+        .unwrap();
+
+    dispatcher
+        .run_command_in_context("heal", Box::new(|_| "alice".to_string()))
+        .unwrap();
+    dispatcher
+        .run_command_in_context("heal", Box::new(|_| "bob".to_string()))
+        .unwrap();
+    assert_eq!(
+        dispatcher.run_command_in_context("heal", Box::new(|_| "alice".to_string())),
+        Err(Error::Cooldown(Duration::from_secs(10)))
+    );
+}
+
+#[test]
+fn rate_limit() {
+    let clock = ManualClock::new();
+    let dispatcher = Dispatcher::builder()
+        .base_context(())
+        .context_factory(|_| ())
+        .clock(clock.clone())
+        .rate_limit(2, Duration::from_secs(60))
+        .child(Command::literal("ping").exec(|_| Ok(())))
+        .build()
+        .unwrap();
+
+    dispatcher.run_command("ping").unwrap();
+    clock.advance(Duration::from_secs(20));
+    dispatcher.run_command("ping").unwrap();
+    assert_eq!(
+        dispatcher.run_command("ping"),
+        Err(Error::RateLimited(Duration::from_secs(40)))
+    );
+    clock.advance(Duration::from_secs(40));
+    dispatcher.run_command("ping").unwrap();
+}
+
+#[test]
+#[should_panic(expected = "rate limit count must be at least 1")]
+fn rate_limit_zero() {
+    let _ = Dispatcher::<(), (), ()>::builder().rate_limit(0, Duration::from_secs(60));
+}
+
+#[test]
+fn middleware() {
+    let dispatcher =
+        Dispatcher::builder()
+            .base_context(())
+            .context_factory(|_| Vec::<String>::new())
+            .middleware(|ctx: &mut ExecContext<Vec<String>>, next: Next<_, _>| {
+                let path = ctx.path().join(" ");
+                ctx.context_mut().push(format!("before {path}"));
+                next.run(ctx).map(|output: String| output.to_uppercase())
+            })
+            .middleware(|ctx: &mut ExecContext<Vec<String>>, next: Next<_, _>| {
+                if ctx
+                    .arguments()
+                    .any(|(name, value)| name == "num" && value == "0")
+                {
+                    return Err(Error::ExecutionFailed);
+                }
+                next.run(ctx)
+            })
+            .child(Command::literal("echo").child(
+                Command::argument("num", IntArgument, true).exec(|ctx| {
+                    assert_eq!(ctx.context(), &vec!["before echo num".to_string()]);
+                    Ok(format!("echo {}", ctx.get("num", IntArgument)?))
+                }),
+            ))
+            .build()
+            .unwrap();
+
+    assert_eq!(
+        dispatcher.run_command("echo 3"),
+        Ok(vec!["ECHO 3".to_string()])
+    );
+    assert_eq!(
+        dispatcher.run_command("echo 0"),
+        Err(Error::ExecutionFailed)
+    );
+}
+
+type Level = Arc<Mutex<Vec<i32>>>;
+
+#[test]
+fn undo_redo() {
+    let level = Level::default();
+    let dispatcher = Dispatcher::builder()
+        .base_context(level.clone())
+        .context_factory(|level: &Level| level.clone())
+        .undo(2)
+        .child(
+            Command::literal("place").child(Command::argument("block", IntArgument, true).exec(
+                |ctx: &mut ExecContext<Level>| {
+                    ctx.context()
+                        .lock()
+                        .unwrap()
+                        .push(ctx.get("block", IntArgument)?);
+                    ctx.on_undo(|ctx| {
+                        let mut level = ctx.context().lock().unwrap();
+                        // negative blocks cannot be removed
+                        if level.last().is_some_and(|block| *block < 0) {
+                            return Err(Error::ExecutionFailed);
+                        }
+                        level.pop();
+                        Ok(())
+                    });
+                    Ok(())
+                },
+            )),
+        )
+        .child(Command::literal("look").exec(|_| Ok(())))
+        .build()
+        .unwrap();
+
+    dispatcher.run_command("place 1; place 2; place 3").unwrap();
+    dispatcher.run_command("undo; undo").unwrap();
+    assert_eq!(*level.lock().unwrap(), vec![1]);
+    // history is bounded to two commands
+    assert_eq!(dispatcher.run_command("undo"), Err(Error::EmptyHistory));
+
+    dispatcher.run_command("look; redo").unwrap();
+    assert_eq!(*level.lock().unwrap(), vec![1, 2]);
+    dispatcher.run_command("undo; redo; redo").unwrap();
+    assert_eq!(*level.lock().unwrap(), vec![1, 2, 3]);
+    assert_eq!(dispatcher.run_command("redo"), Err(Error::EmptyHistory));
+
+    dispatcher.run_command("undo; place 4").unwrap();
+    assert_eq!(dispatcher.run_command("redo"), Err(Error::EmptyHistory));
+    assert_eq!(*level.lock().unwrap(), vec![1, 2, 4]);
+
+    // a failed undo is not redone
+    dispatcher.run_command("place -1").unwrap();
+    assert_eq!(dispatcher.run_command("undo"), Err(Error::ExecutionFailed));
+    assert_eq!(dispatcher.run_command("redo"), Err(Error::EmptyHistory));
+    assert_eq!(*level.lock().unwrap(), vec![1, 2, 4, -1]);
+}
+
+#[test]
+fn completion() {
+    let dispatcher = Dispatcher::builder()
+        .prefix("/")
+        .base_context(())
+        .context_factory(|_| ())
+        .child(
+            Command::literal("give").child(
+                Command::argument("amount", IntArgument, false)
+                    .child(Command::literal("gold").exec(|_| Ok(())))
+                    .child(Command::literal("gems").exec(|_| Ok(()))),
+            ),
+        )
+        .child(Command::literal("gamemode").exec(|_| Ok(())))
+        .child(Command::literal("help").exec(|_| Ok(())))
+        .build()
+        .unwrap();
+
+    let completions = |candidates: &[&str], start| Completions {
+        start,
+        candidates: candidates.iter().map(|c| c.to_string()).collect(),
+    };
+    assert_eq!(dispatcher.complete(""), completions(&["/"], 0));
+    assert_eq!(
+        dispatcher.complete("/"),
+        completions(&["gamemode", "give", "help"], 1)
+    );
+    assert_eq!(
+        dispatcher.complete("/g"),
+        completions(&["gamemode", "give"], 1)
+    );
+    assert_eq!(
+        dispatcher.complete("/give 5 g"),
+        completions(&["gems", "gold"], 8)
+    );
+    assert_eq!(dispatcher.complete("/give go"), completions(&["gold"], 6));
+    assert_eq!(dispatcher.complete("/help; gi"), completions(&["give"], 7));
+    assert_eq!(dispatcher.complete("/help "), completions(&[], 6));
+}
+
+#[test]
+fn incomplete_input() {
+    assert!(!is_incomplete(r#"say "hello""#, Dialect::default()));
+    assert!(is_incomplete(r#"say "hello"#, Dialect::default()));
+    assert!(is_incomplete(
+        r#"say text="hello \" world"#,
+        Dialect::default()
+    ));
+    assert!(!is_incomplete(r#"say don"t"#, Dialect::default()));
+}
+
+#[test]
+fn tokenize_dialect() {
+    let sample = r##"open 'C:\temp' r#"say "hi""# path='a b' echo\;done"##;
+    assert_eq!(
+        tokens_in(sample, Dialect::extended()),
+        vec![
+            simple("open"),
+            simple("C:\\temp"),
+            simple(r#"say "hi""#),
+            named("path", "a b"),
+            simple("echo;done"),
+        ]
+    );
+    assert_eq!(
+        tokens(r"'a b' c\;d"),
+        vec![
+            simple("'a"),
+            simple("b'"),
+            simple("c\\"),
+            end(),
+            simple("d")
+        ]
+    );
+}
+
+#[test]
+fn dispatcher_dialect() {
+    let dispatcher = Dispatcher::builder()
+        .base_context(())
+        .context_factory(|_| ())
+        .dialect(Dialect::extended())
+        .child(
+            Command::literal("echo").child(
+                Command::argument("text", StringArgument, true)
+                    .exec(|ctx| ctx.get("text", StringArgument)),
+            ),
+        )
+        .build()
+        .unwrap();
+
+    assert_eq!(
+        dispatcher.run_command(r"echo 'C:\temp'; echo a\;b"),
+        Ok(vec!["C:\\temp".to_string(), "a;b".to_string()])
+    );
+    assert!(dispatcher.is_incomplete("echo 'abc"));
+    assert!(dispatcher.is_incomplete(r##"echo r#"abc"##));
+    assert!(!dispatcher.is_incomplete(r##"echo r#"abc"# 'd'"##));
+    // only a single `r` starts a raw string
+    assert!(!dispatcher.is_incomplete(r#"echo rr"x""#));
+    assert!(!dispatcher.is_incomplete(r#"echo r#r"x"#));
+}
+
+/// Tokenizes `data`, dropping spans.
+fn tokens_in(data: &str, dialect: Dialect) -> Vec<Token<'_>> {
+    let unspanned = |token: Spanned<_>| Spanned::new(token.value, Span::default());
+    tokenize(data, dialect)
+        .unwrap()
+        .1
+        .into_iter()
+        .map(|token| match token {
+            Token::Simple(value) => Token::Simple(unspanned(value)),
+            Token::Named(key, value) => Token::Named(unspanned(key), unspanned(value)),
+            Token::End(_) => end(),
+        })
+        .collect()
+}
+
+fn tokens(data: &str) -> Vec<Token<'_>> {
+    tokens_in(data, Dialect::default())
+}
+
+fn simple(value: &str) -> Token<'_> {
+    Token::Simple(Spanned::new(Cow::Borrowed(value), Span::default()))
+}
+
+fn named<'a>(key: &'a str, value: &'a str) -> Token<'a> {
+    Token::Named(
+        Spanned::new(Cow::Borrowed(key), Span::default()),
+        Spanned::new(Cow::Borrowed(value), Span::default()),
+    )
+}
+
+fn end() -> Token<'static> {
+    Token::End(Span::default())
+}
+
+#[test]
+fn named_argument_keys() {
+    assert_eq!(tokens("max-count=5"), vec![named("max-count", "5")]);
+    assert_eq!(tokens("user_id=7"), vec![named("user_id", "7")]);
+    assert_eq!(tokens("_private=1"), vec![named("_private", "1")]);
+    assert_eq!(tokens("größe=3"), vec![named("größe", "3")]);
+    assert_eq!(tokens("名前=値"), vec![named("名前", "値")]);
+    assert_eq!(tokens("a1=b"), vec![named("a1", "b")]);
+}
+
+#[test]
+fn named_argument_values() {
+    assert_eq!(tokens("offset=-3"), vec![named("offset", "-3")]);
+    assert_eq!(tokens("path=/a/b"), vec![named("path", "/a/b")]);
+    assert_eq!(tokens("ratio=0.5"), vec![named("ratio", "0.5")]);
+    assert_eq!(tokens("expr=a=b"), vec![named("expr", "a=b")]);
+    assert_eq!(
+        tokens("url=https://x.org/?q=1"),
+        vec![named("url", "https://x.org/?q=1")]
+    );
+    assert_eq!(tokens(r#"msg="a b""#), vec![named("msg", "a b")]);
+    assert_eq!(tokens(r#"msg="""#), vec![named("msg", "")]);
+    assert_eq!(
+        tokens("a=1;b=2"),
+        vec![named("a", "1"), end(), named("b", "2")]
+    );
+}
+
+#[test]
+fn bracketed_tokens() {
+    // brackets are left to arguments reading the raw input, so `;` always ends the command
+    assert_eq!(
+        tokens("say [oops; ping"),
+        vec![simple("say"), simple("[oops"), end(), simple("ping")]
+    );
+    assert_eq!(
+        tokens(r#"{"b":"c; d"}"#),
+        vec![simple(r#"{"b":"c"#), end(), simple(r#"d"}"#)]
+    );
+}
+
+#[test]
+fn positional_lookalikes() {
+    // keys must start with a letter or underscore
+    assert_eq!(tokens("-3=x"), vec![simple("-3=x")]);
+    assert_eq!(tokens("--flag=x"), vec![simple("--flag=x")]);
+    assert_eq!(tokens("=x"), vec![simple("=x")]);
+    assert_eq!(tokens("1=x"), vec![simple("1=x")]);
+    // keys end at the first `=` and cannot contain other punctuation
+    assert_eq!(tokens("a.b=c"), vec![simple("a.b=c")]);
+    assert_eq!(
+        tokens("https://x.org/?q=1"),
+        vec![simple("https://x.org/?q=1")]
+    );
+    // a key needs a value
+    assert_eq!(tokens("key="), vec![simple("key=")]);
+    assert_eq!(tokens("key= value"), vec![simple("key="), simple("value")]);
+}
+
+#[test]
+fn spans() {
+    let input = "/größe give amount=5 \"gold bar\"";
+    let dispatcher =
+        Dispatcher::builder()
+            .prefix("/")
+            .base_context(())
+            .context_factory(|_| ())
+            .child(Command::literal("größe").child(
+                Command::literal("give").child(
+                    Command::argument("amount", IntArgument, true).child(
+                        Command::argument("item", StringArgument, true).exec(|ctx| {
+                            Ok([ctx.span("größe"), ctx.span("amount"), ctx.span("item")])
+                        }),
+                    ),
+                ),
+            ))
+            .build()
+            .unwrap();
+
+    let spans = dispatcher.run_command(input).unwrap().remove(0);
+    assert_eq!(
+        spans,
+        [
+            Some(Span::new(1, 8)),
+            Some(Span::new(21, 22)),
+            Some(Span::new(23, 33))
+        ]
+    );
+    assert_eq!(spans[0].unwrap().slice(input), "größe");
+    assert_eq!(spans[0].unwrap().char_range(input), 1..6);
+    assert_eq!(spans[2].unwrap().char_range(input), 21..31);
+}
+
+#[test]
+fn highlight() {
+    let dispatcher = Dispatcher::builder()
+        .prefix("/")
+        .base_context(())
+        .context_factory(|_| ())
+        .child(
+            Command::literal("give").child(
+                Command::argument("amount", IntArgument, true)
+                    .child(Command::argument("item", StringArgument, true).exec(|_| Ok(()))),
+            ),
+        )
+        .child(Command::literal("ping").exec(|_| Ok(())))
+        .build()
+        .unwrap();
+
+    let highlight = |start, end, kind| Highlight {
+        span: Span::new(start, end),
+        kind,
+    };
+    let argument = |name: &str, type_name: &str| HighlightKind::Argument {
+        name: name.to_string(),
+        type_name: Some(type_name.to_string()),
+    };
+    assert_eq!(
+        dispatcher.highlight("/give 5 gold; ping extra; give count=2"),
+        vec![
+            highlight(0, 1, HighlightKind::Prefix),
+            highlight(1, 5, HighlightKind::Literal),
+            highlight(6, 7, argument("amount", "i32")),
+            highlight(8, 12, argument("item", "String")),
+            highlight(12, 13, HighlightKind::Separator),
+            highlight(14, 18, HighlightKind::Literal),
+            highlight(19, 24, HighlightKind::Error),
+            highlight(24, 25, HighlightKind::Separator),
+            highlight(26, 30, HighlightKind::Literal),
+            highlight(31, 38, HighlightKind::Error),
+        ]
+    );
+    assert_eq!(
+        dispatcher.highlight("/give amount=5 gold"),
+        vec![
+            highlight(0, 1, HighlightKind::Prefix),
+            highlight(1, 5, HighlightKind::Literal),
+            highlight(
+                6,
+                12,
+                HighlightKind::NamedKey {
+                    name: "amount".to_string()
+                }
+            ),
+            highlight(
+                13,
+                14,
+                HighlightKind::NamedValue {
+                    name: "amount".to_string(),
+                    type_name: Some("i32".to_string())
+                }
+            ),
+            highlight(15, 19, argument("item", "String")),
+        ]
+    );
+    // an invalid argument is an error, as is everything after it
+    assert_eq!(
+        dispatcher.highlight("/give x gold"),
+        vec![
+            highlight(0, 1, HighlightKind::Prefix),
+            highlight(1, 5, HighlightKind::Literal),
+            highlight(6, 7, HighlightKind::Error),
+            highlight(8, 12, HighlightKind::Error),
+        ]
+    );
+    assert_eq!(dispatcher.highlight("give"), vec![]);
+
+    #[derive(Debug, Clone)]
+    struct Typed<T>(std::marker::PhantomData<T>);
+
+    impl<T: std::fmt::Debug + Clone> ArgumentParser for Typed<T> {
+        type Output = T;
+
+        fn parse(&self, _token: &str) -> crate::Result<T> {
+            Err(Error::InvalidCommand(InvalidCommandReason::InvalidArgument))
+        }
+    }
+
+    assert_eq!(
+        Typed::<Vec<std::path::PathBuf>>(Default::default()).type_name(),
+        "Vec<PathBuf>"
+    );
+    assert_eq!(
+        Typed::<Option<(u8, IpAddr)>>(Default::default()).type_name(),
+        "Option<(u8, IpAddr)>"
+    );
+    assert_eq!(
+        Typed::<[Duration; 2]>(Default::default()).type_name(),
+        "[Duration; 2]"
+    );
+    assert_eq!(
+        Argument::with_parser(
+            Typed::<Vec<std::path::PathBuf>>(Default::default()),
+            "paths".to_string(),
+            true
+        )
+        .type_name(),
+        Some("Vec<PathBuf>")
+    );
+}
+
+/// Counts the allocations made by the current thread.
+struct CountingAllocator;
+
+thread_local! {
+    static ALLOCATIONS: Cell<usize> = const { Cell::new(0) };
+}
+
+unsafe impl GlobalAlloc for CountingAllocator {
+    unsafe fn alloc(&self, layout: Layout) -> *mut u8 {
+        ALLOCATIONS.with(|count| count.set(count.get() + 1));
+        System.alloc(layout)
+    }
+
+    unsafe fn dealloc(&self, ptr: *mut u8, layout: Layout) {
+        System.dealloc(ptr, layout)
+    }
+}
+
+#[global_allocator]
+static ALLOCATOR: CountingAllocator = CountingAllocator;
+
+fn allocations(f: impl FnOnce()) -> usize {
+    let before = ALLOCATIONS.with(Cell::get);
+    f();
+    ALLOCATIONS.with(Cell::get) - before
+}
+
+#[test]
+fn borrowed_tokens() {
+    let is_borrowed = |token: &Token| match token {
+        Token::Simple(value) => matches!(value.value, Cow::Borrowed(_)),
+        Token::Named(key, value) => {
+            matches!(key.value, Cow::Borrowed(_)) && matches!(value.value, Cow::Borrowed(_))
+        }
+        Token::End(_) => true,
+    };
+    let (_, tokens) = tokenize(
+        r##"say "hello world" to=r#"x"# 'y'; z\;"##,
+        Dialect::extended(),
+    )
+    .unwrap();
+    assert!(tokens[..5].iter().all(is_borrowed));
+    assert!(!is_borrowed(&tokens[5]));
+
+    let (_, tokens) = tokenize(r#"say "escaped \"quote\"""#, Dialect::default()).unwrap();
+    assert!(!is_borrowed(&tokens[1]));
+    assert_eq!(
+        tokens[1].clone().into_owned(),
+        Token::Simple(Spanned::new(
+            Cow::Borrowed(r#"escaped "quote""#),
+            Span::new(4, 23)
+        ))
+    );
+}
+
+#[test]
+fn dispatch_without_allocation() {
+    let dispatcher = Dispatcher::builder()
+        .prefix("/")
+        .base_context(())
+        .context_factory(|_| ())
+        .child(
+            Command::literal("give").child(
+                Command::argument("amount", IntArgument, true)
+                    .exec(|_| Ok(()))
+                    .child(Command::argument("item", IntArgument, true).exec(|ctx| {
+                        ctx.get("amount", IntArgument)?;
+                        Ok(())
+                    })),
+            ),
+        )
+        .build()
+        .unwrap();
+
+    dispatcher.run_command("/give 5").unwrap();
+    assert_eq!(
+        allocations(|| {
+            dispatcher.run_command("/give 5").unwrap();
+            dispatcher
+                .run_command(r#"/give "5" item=7; give 1 2"#)
+                .unwrap();
+        }),
+        0
+    );
+}
+
+#[test]
+fn indexed_literals() {
+    let mut builder = Dispatcher::builder()
+        .base_context(())
+        .context_factory(|_| ())
+        .matching(LiteralMatching::AsciiCaseInsensitive);
+    for i in 0..5000 {
+        builder = builder
+            .child(Command::literal(format!("cmd{i}")).exec(|ctx| Ok(ctx.path()[0].to_string())));
+    }
+    let dispatcher = builder
+        .child(
+            Command::literal("Mode")
+                .matching(LiteralMatching::CaseSensitive)
+                .exec(|_| Ok("Mode".to_string())),
+        )
+        .child(Command::literal("mode").exec(|_| Ok("mode".to_string())))
+        .child(
+            Command::literal("\u{212a}elvin")
+                .matching(LiteralMatching::UnicodeCaseFold)
+                .exec(|_| Ok("kelvin".to_string())),
+        )
+        .child(
+            Command::argument("fallback", StringArgument, true)
+                .exec(|ctx| ctx.get("fallback", StringArgument)),
+        )
+        .build()
+        .unwrap();
+
+    assert_eq!(
+        dispatcher.run_command("cmd4321"),
+        Ok(vec!["cmd4321".to_string()])
+    );
+    assert_eq!(
+        dispatcher.run_command("CMD17"),
+        Ok(vec!["cmd17".to_string()])
+    );
+    // literals sharing a folded name are still told apart by their own policy
+    assert_eq!(dispatcher.run_command("Mode"), Ok(vec!["Mode".to_string()]));
+    assert_eq!(dispatcher.run_command("MODE"), Ok(vec!["mode".to_string()]));
+    assert_eq!(
+        dispatcher.run_command("KELVIN"),
+        Ok(vec!["kelvin".to_string()])
+    );
+    assert_eq!(
+        dispatcher.run_command("cmd5000"),
+        Ok(vec!["cmd5000".to_string()])
+    );
+}
+
+#[test]
+fn number_arguments() {
+    assert_eq!(U8Argument::new().parse("255"), Ok(255));
+    assert!(U8Argument::new().parse("256").is_err());
+    assert!(U8Argument::new().parse("-1").is_err());
+
+    let volume = U8Argument::new().min(1).max(10);
+    assert_eq!(volume.parse("10"), Ok(10));
+    assert_eq!(
+        volume.parse("0"),
+        Err(Error::InvalidCommand(InvalidCommandReason::InvalidArgument))
+    );
+
+    let ratio = F64Argument::new().min(0.0).max(1.0);
+    assert_eq!(ratio.parse("0.25"), Ok(0.25));
+    assert!(ratio.parse("1.5").is_err());
+    assert!(ratio.parse("NaN").is_err());
+    assert_eq!(F64Argument::new().type_name(), "f64");
+}
+
+#[test]
+fn bool_and_from_str_arguments() {
+    for (token, value) in [("true", true), ("No", false), ("ON", true), ("off", false)] {
+        assert_eq!(BoolArgument.parse(token), Ok(value));
+    }
+    assert!(BoolArgument.parse("1").is_err());
+    assert_eq!(BoolArgument.type_name(), "bool");
+
+    let address = FromStrArgument::<std::net::Ipv4Addr>::new();
+    assert_eq!(
+        address.parse("127.0.0.1"),
+        Ok(std::net::Ipv4Addr::LOCALHOST)
+    );
+    assert_eq!(address.type_name(), "Ipv4Addr");
+
+    let dispatcher = Dispatcher::builder()
+        .base_context(())
+        .context_factory(|_| ())
+        .child(
+            Command::literal("set").child(
+                Command::argument("enabled", BoolArgument, true)
+                    .exec(|ctx| ctx.get("enabled", BoolArgument)),
+            ),
+        )
+        .build()
+        .unwrap();
+    assert_eq!(dispatcher.run_command("set yes"), Ok(vec![true]));
+    assert_eq!(
+        dispatcher.run_command("set maybe"),
+        Err(Error::InvalidCommand(InvalidCommandReason::UnknownCommand))
+    );
+}
+
+#[test]
+fn stateful_validators() {
+    let dispatcher = Dispatcher::builder()
+        .base_context(())
+        .context_factory(|_| ())
+        .child(
+            Command::literal("difficulty")
+                .child(
+                    Command::argument("level", ChoiceArgument::new(["easy", "hard"]), true)
+                        .exec(|ctx| ctx.get("level", StringArgument)),
+                )
+                .child(
+                    Command::argument("custom", BoundedIntArgument::new(1, 10), true)
+                        .exec(|ctx| Ok(ctx.get("custom", IntArgument)?.to_string())),
+                ),
+        )
+        .build()
+        .unwrap();
+
+    assert_eq!(
+        dispatcher.run_command("difficulty hard"),
+        Ok(vec!["hard".to_string()])
+    );
+    assert_eq!(
+        dispatcher.run_command("difficulty 7"),
+        Ok(vec!["7".to_string()])
+    );
+    for input in ["difficulty medium", "difficulty 11"] {
+        assert_eq!(
+            dispatcher.run_command(input),
+            Err(Error::InvalidCommand(InvalidCommandReason::UnknownCommand))
+        );
+    }
+
+    assert_eq!(
+        dispatcher.complete("difficulty "),
+        Completions {
+            start: 11,
+            candidates: vec!["easy".to_string(), "hard".to_string()]
+        }
+    );
+    assert_eq!(
+        dispatcher.complete("difficulty h").candidates,
+        vec!["hard".to_string()]
+    );
+    assert_eq!(
+        dispatcher.usage(),
+        vec!["difficulty <easy|hard>", "difficulty <custom>"]
+    );
+}
+
+#[test]
+fn duration_argument() {
+    let parser = DurationArgument::new();
+    assert_eq!(parser.parse("90s"), Ok(Duration::from_secs(90)));
+    assert_eq!(parser.parse("1h30m"), Ok(Duration::from_secs(5400)));
+    assert_eq!(parser.parse("2d"), Ok(Duration::from_secs(172800)));
+    assert_eq!(parser.parse("250ms"), Ok(Duration::from_millis(250)));
+    for invalid in ["", "90", "h", "1x", "1h30", "-5s"] {
+        assert!(parser.parse(invalid).is_err(), "{invalid}");
+    }
+
+    let mute = DurationArgument::new()
+        .units([
+            ("s", Duration::from_secs(1)),
+            ("m", Duration::from_secs(60)),
+        ])
+        .min(Duration::from_secs(10))
+        .max(Duration::from_secs(3600));
+    assert!(mute.parse("1h").is_err());
+    assert!(mute.parse("5s").is_err());
+    assert!(mute.parse("61m").is_err());
+    assert_eq!(mute.parse("60m"), Ok(Duration::from_secs(3600)));
+
+    let dispatcher = Dispatcher::builder()
+        .base_context(())
+        .context_factory(|_| ())
+        .child(
+            Command::literal("mute").child(
+                Command::argument("duration", mute, true)
+                    .exec(|ctx| ctx.get("duration", DurationArgument::new())),
+            ),
+        )
+        .build()
+        .unwrap();
+    assert_eq!(
+        dispatcher.run_command("mute 1m30s"),
+        Ok(vec![Duration::from_secs(90)])
+    );
+    // units are only suggested if the result is within bounds
+    assert_eq!(
+        dispatcher.complete("mute 30").candidates,
+        vec!["30m".to_string(), "30s".to_string()]
+    );
+    assert_eq!(
+        dispatcher.complete("mute 90").candidates,
+        vec!["90s".to_string()]
+    );
+    assert_eq!(
+        dispatcher.complete("mute 1m").candidates,
+        Vec::<String>::new()
+    );
+}
+
+#[derive(Debug, Clone, Copy, PartialEq)]
+enum GameMode {
+    Survival,
+    Creative,
+    Spectator,
+}
+
+impl ArgumentEnum for GameMode {
+    const VARIANTS: &'static [(Self, &'static [&'static str])] = &[
+        (GameMode::Survival, &["survival", "s", "0"]),
+        (GameMode::Creative, &["creative", "c", "1"]),
+        (GameMode::Spectator, &["spectator", "sp", "3"]),
+    ];
+}
+
+#[test]
+fn enum_argument() {
+    let dispatcher = Dispatcher::builder()
+        .base_context(())
+        .context_factory(|_| ())
+        .child(
+            Command::literal("gamemode").child(
+                Command::argument("mode", EnumArgument::<GameMode>::new(), true)
+                    .exec(|ctx| ctx.get("mode", EnumArgument::new())),
+            ),
+        )
+        .build()
+        .unwrap();
+
+    assert_eq!(
+        dispatcher.run_command("gamemode creative"),
+        Ok(vec![GameMode::Creative])
+    );
+    assert_eq!(
+        dispatcher.run_command("gamemode 3"),
+        Ok(vec![GameMode::Spectator])
+    );
+    assert_eq!(
+        dispatcher.run_command("gamemode adventure"),
+        Err(Error::InvalidCommand(InvalidCommandReason::UnknownCommand))
+    );
+    // aliases are accepted but not suggested
+    assert_eq!(
+        dispatcher.complete("gamemode s").candidates,
+        vec!["spectator".to_string(), "survival".to_string()]
+    );
+    assert_eq!(
+        dispatcher.usage(),
+        vec!["gamemode <survival|creative|spectator>"]
+    );
+    assert_eq!(EnumArgument::<GameMode>::new().type_name(), "GameMode");
+}
+
+#[derive(Debug)]
+struct Player {
+    position: [f64; 3],
+    rotation: [f64; 2],
+}
+
+impl Origin for Player {
+    fn position(&self) -> [f64; 3] {
+        self.position
+    }
+
+    fn rotation(&self) -> [f64; 2] {
+        self.rotation
+    }
+}
+
+#[test]
+fn coordinate_arguments() {
+    let dispatcher = Dispatcher::builder()
+        .base_context([0.0, 0.0])
+        .context_factory(|rotation| Player {
+            position: [10.0, 64.0, -5.0],
+            rotation: *rotation,
+        })
+        .child(
+            Command::literal("tp").child(
+                Command::argument("position", Vec3Argument, true)
+                    .exec(|ctx| {
+                        let position = ctx.get("position", Vec3Argument)?;
+                        Ok(position.resolve(ctx.context()).to_vec())
+                    })
+                    .child(
+                        Command::argument("facing", RotationArgument, true).exec(|ctx| {
+                            let rotation = ctx.get("facing", RotationArgument)?;
+                            Ok(rotation.resolve(ctx.context()).to_vec())
+                        }),
+                    ),
+            ),
+        )
+        .child(Command::literal("chunk").child(
+            Command::argument("column", Vec2Argument, true).exec(|ctx| {
+                let column = ctx.get("column", Vec2Argument)?;
+                Ok(column.resolve(ctx.context()).to_vec())
+            }),
+        ))
+        .build()
+        .unwrap();
+
+    assert_eq!(
+        dispatcher.run_command("tp 1 ~ ~-2.5"),
+        Ok(vec![vec![1.0, 64.0, -7.5]])
+    );
+    assert_eq!(
+        dispatcher.run_command("tp ~ ~ ~ ~90 -45"),
+        Ok(vec![vec![90.0, -45.0]])
+    );
+    assert_eq!(
+        dispatcher.run_command("chunk ~16 3"),
+        Ok(vec![vec![26.0, 3.0]])
+    );
+    // facing +z, left is +x
+    assert_eq!(
+        dispatcher.run_command("tp ^2 ^1 ^"),
+        Ok(vec![vec![12.0, 65.0, -5.0]])
+    );
+    assert_eq!(
+        dispatcher.run_command(r#"tp position="~1 ~ ~" ~5 10"#),
+        Ok(vec![vec![5.0, 10.0]])
+    );
+    for input in ["tp 1 2", "tp ^ ~ ^", "tp 1 2 z", "chunk 1 2 3"] {
+        assert_eq!(
+            dispatcher.run_command(input),
+            Err(Error::InvalidCommand(InvalidCommandReason::UnknownCommand)),
+            "{input}"
+        );
+    }
+
+    assert_eq!(
+        Vec3Argument.parse("^ ^ ^5"),
+        Ok(Vec3::Local([0.0, 0.0, 5.0]))
+    );
+    let facing_west = Player {
+        position: [0.0, 0.0, 0.0],
+        rotation: [90.0, 0.0],
+    };
+    let [x, y, z] = Vec3::Local([0.0, 0.0, 1.0]).resolve(&facing_west);
+    assert!((x + 1.0).abs() < 1e-9 && y.abs() < 1e-9 && z.abs() < 1e-9);
+    assert!(RotationArgument.parse("~ ~ ~").is_err());
+    assert_eq!(
+        RotationArgument.parse("~ 10"),
+        Ok(Rotation([
+            Coordinate::Relative(0.0),
+            Coordinate::Absolute(10.0)
+        ]))
+    );
+
+    let highlights = dispatcher.highlight("tp 1 2 3 ~ ~");
+    assert_eq!(
+        highlights
+            .iter()
+            .map(|highlight| highlight.span.start)
+            .collect::<Vec<_>>(),
+        vec![0, 3, 5, 7, 9, 11]
+    );
+    assert_eq!(
+        highlights[3].kind,
+        HighlightKind::Argument {
+            name: "position".to_string(),
+            type_name: Some("vec3".to_string())
+        }
+    );
+    assert_eq!(
+        dispatcher.usage(),
+        vec!["tp <x y z>", "tp <x y z> <yaw pitch>", "chunk <x z>"]
+    );
+}
+
+/// Leading digits of a token, leaving the rest for the next node.
+#[derive(Debug, Clone)]
+struct DigitsArgument;
+
+impl ArgumentParser for DigitsArgument {
+    type Output = u32;
+
+    fn parse(&self, token: &str) -> crate::Result<Self::Output> {
+        token
+            .parse()
+            .map_err(|_| Error::InvalidCommand(InvalidCommandReason::InvalidArgument))
+    }
+
+    fn reads_input(&self) -> bool {
+        true
+    }
+
+    fn read(&self, reader: &mut StringReader) -> crate::Result<()> {
+        match reader.read_while(|c| c.is_ascii_digit()).is_empty() {
+            true => Err(Error::InvalidCommand(InvalidCommandReason::InvalidArgument)),
+            false => Ok(()),
+        }
+    }
+}
+
+/// A `key: value` pair spanning one to three tokens.
+#[derive(Debug, Clone)]
+struct PairArgument;
+
+impl ArgumentParser for PairArgument {
+    type Output = (String, String);
+
+    fn parse(&self, token: &str) -> crate::Result<Self::Output> {
+        match token.split_once(':') {
+            Some((key, value)) if !key.trim().is_empty() && !value.trim().is_empty() => {
+                Ok((key.trim().to_string(), value.trim().to_string()))
+            }
+            _ => Err(Error::InvalidCommand(InvalidCommandReason::InvalidArgument)),
+        }
+    }
+
+    fn reads_input(&self) -> bool {
+        true
+    }
+
+    fn read(&self, reader: &mut StringReader) -> crate::Result<()> {
+        reader.read_while(|c| !c.is_whitespace() && c != ':');
+        reader.skip_whitespace();
+        reader.expect(':')?;
+        reader.skip_whitespace();
+        reader.read_word().map(|_| ())
+    }
+}
+
+#[test]
+fn arguments_reading_input() {
+    let dispatcher = Dispatcher::builder()
+        .base_context(())
+        .context_factory(|_| ())
+        .child(
+            Command::literal("scale").child(
+                Command::argument("factor", DigitsArgument, true)
+                    .child(
+                        Command::literal("%")
+                            .exec(|ctx| Ok(format!("{}/100", ctx.get("factor", DigitsArgument)?))),
+                    )
+                    .child(
+                        Command::literal("x")
+                            .exec(|ctx| Ok(format!("{}", ctx.get("factor", DigitsArgument)?))),
+                    ),
+            ),
+        )
+        .child(
+            Command::literal("set").child(Command::argument("pair", PairArgument, true).exec(
+                |ctx| {
+                    let (key, value) = ctx.get("pair", PairArgument)?;
+                    Ok(format!("{key}={value}"))
+                },
+            )),
+        )
+        .build()
+        .unwrap();
+
+    assert_eq!(
+        dispatcher.run_command("scale 50%"),
+        Ok(vec!["50/100".to_string()])
+    );
+    assert_eq!(
+        dispatcher.run_command("scale 2 x"),
+        Ok(vec!["2".to_string()])
+    );
+    for input in ["set a:b", "set a: b", "set a :b", "set a : b"] {
+        assert_eq!(
+            dispatcher.run_command(input),
+            Ok(vec!["a=b".to_string()]),
+            "{input}"
+        );
+    }
+    for input in [
+        "scale 2",
+        "scale x",
+        r#"scale "2x""#,
+        "scale 2%x",
+        "set a b",
+        "set a: b c",
+    ] {
+        assert_eq!(
+            dispatcher.run_command(input),
+            Err(Error::InvalidCommand(InvalidCommandReason::UnknownCommand)),
+            "{input}"
+        );
+    }
+
+    let highlight = |start, end, kind| Highlight {
+        span: Span::new(start, end),
+        kind,
+    };
+    let factor = HighlightKind::Argument {
+        name: "factor".to_string(),
+        type_name: Some("u32".to_string()),
+    };
+    assert_eq!(
+        dispatcher.highlight("scale 50%x"),
+        vec![
+            highlight(0, 5, HighlightKind::Literal),
+            highlight(6, 8, factor.clone()),
+            highlight(8, 10, HighlightKind::Error),
+        ]
+    );
+    assert_eq!(
+        dispatcher.highlight("scale 5 x"),
+        vec![
+            highlight(0, 5, HighlightKind::Literal),
+            highlight(6, 7, factor),
+            highlight(8, 9, HighlightKind::Literal),
+        ]
+    );
+}
+
+#[test]
+fn reader_positions() {
+    let mut reader = StringReader::new("set é", 4);
+    assert!(reader.set_position(3).is_err());
+    assert!(reader.set_position(5).is_err());
+    assert!(reader.set_position(7).is_err());
+    assert_eq!(reader.set_position(6), Ok(()));
+    assert_eq!(reader.remaining(), "");
+
+    #[derive(Debug, Clone)]
+    struct Jump(fn(&mut StringReader) -> crate::Result<()>);
+
+    impl ArgumentParser for Jump {
+        type Output = String;
+
+        fn parse(&self, token: &str) -> crate::Result<String> {
+            Ok(token.to_string())
+        }
+
+        fn reads_input(&self) -> bool {
+            true
+        }
+
+        fn read(&self, reader: &mut StringReader) -> crate::Result<()> {
+            (self.0)(reader)
+        }
+    }
+
+    let jumps: [fn(&mut StringReader) -> crate::Result<()>; 4] = [
+        |reader| reader.set_position(reader.position() - 1),
+        |reader| reader.set_position(1000),
+        |reader| reader.set_position(reader.position() + 1),
+        |reader| {
+            *reader = StringReader::new("a much longer input than the command", 30);
+            Ok(())
+        },
+    ];
+    for jump in jumps {
+        let dispatcher = Dispatcher::builder()
+            .base_context(())
+            .context_factory(|_| ())
+            .child(
+                Command::literal("set").child(
+                    Command::argument("value", Jump(jump), true)
+                        .exec(|ctx| ctx.get("value", StringArgument)),
+                ),
+            )
+            .build()
+            .unwrap();
+        assert_eq!(
+            dispatcher.run_command("set é"),
+            Err(Error::InvalidCommand(InvalidCommandReason::UnknownCommand))
+        );
+    }
+}
+
+#[test]
+fn path_argument() {
+    let root = std::env::temp_dir().join(format!("yogurt-path-{}", std::process::id()));
+    std::fs::create_dir_all(root.join("textures")).unwrap();
+    for file in ["logo.png", "notes.txt", ".hidden.png", "textures/grass.png"] {
+        std::fs::write(root.join(file), "").unwrap();
+    }
+
+    let images = PathArgument::new()
+        .root(&root)
+        .kind(PathKind::File)
+        .extensions(["png"]);
+    assert_eq!(images.parse("logo.png"), Ok(root.join("logo.png")));
+    assert_eq!(
+        images.parse("./textures/../textures/grass.PNG"),
+        Ok(root.join("textures/grass.PNG"))
+    );
+    assert_eq!(images.parse("new.png"), Ok(root.join("new.png")));
+    for path in [
+        "notes.txt",
+        "textures",
+        "../logo.png",
+        "textures/../../logo.png",
+        "/etc/passwd",
+        "",
+    ] {
+        assert!(images.parse(path).is_err(), "{path}");
+    }
+    assert!(images.clone().must_exist().parse("new.png").is_err());
+    let directories = PathArgument::new().root(&root).kind(PathKind::Directory);
+    assert_eq!(directories.parse("textures"), Ok(root.join("textures")));
+    assert!(directories.parse("logo.png").is_err());
+    assert_eq!(images.suggest("t"), vec!["textures/"]);
+    // completion resolves its base against the root and stays inside it
+    assert_eq!(
+        images.complete(std::path::Path::new("textures"), "g"),
+        vec!["grass.png"]
+    );
+    assert!(images.complete(&std::env::temp_dir(), "").is_empty());
+    assert!(images.complete(&root, "../").is_empty());
+
+    #[derive(Debug)]
+    struct Console {
+        directory: std::path::PathBuf,
+    }
+
+    let dispatcher = Dispatcher::builder()
+        .base_context(root.clone())
+        .context_factory(|root| Console {
+            directory: root.clone(),
+        })
+        .child(
+            Command::literal("load").child(
+                Command::argument(
+                    "asset",
+                    PathArgument::new().kind(PathKind::File).extensions(["png"]),
+                    true,
+                )
+                .suggestions(|console: &Console, partial| {
+                    PathArgument::new()
+                        .kind(PathKind::File)
+                        .extensions(["png"])
+                        .complete(&console.directory, partial)
+                })
+                .exec(|_| Ok(())),
+            ),
+        )
+        .build()
+        .unwrap();
+
+    let complete = |input: &str| dispatcher.complete(input).candidates;
+    assert_eq!(complete("load "), vec!["logo.png", "textures/"]);
+    assert_eq!(complete("load ."), vec![".hidden.png"]);
+    assert_eq!(complete("load textures/"), vec!["textures/grass.png"]);
+    assert_eq!(complete("load textures/../l"), vec!["textures/../logo.png"]);
+    assert!(complete("load ../").is_empty());
+
+    #[cfg(unix)]
+    {
+        let outside = root.with_file_name(format!("yogurt-outside-{}", std::process::id()));
+        std::fs::create_dir_all(&outside).unwrap();
+        std::fs::write(outside.join("secret.png"), "").unwrap();
+        std::os::unix::fs::symlink(&outside, root.join("link")).unwrap();
+        std::os::unix::fs::symlink(outside.join("missing"), root.join("broken")).unwrap();
+        std::os::unix::fs::symlink(root.join("textures"), root.join("inside")).unwrap();
+
+        let jailed = PathArgument::new().root(&root);
+        for path in [
+            "link",
+            "link/secret.png",
+            "link/new.png",
+            "link/a/b.png",
+            "broken",
+        ] {
+            assert!(jailed.parse(path).is_err(), "{path}");
+        }
+        assert!(jailed.suggest("link/").is_empty());
+        assert_eq!(jailed.suggest("inside/"), vec!["inside/grass.png"]);
+        assert_eq!(
+            jailed.parse("inside/new.png"),
+            Ok(root.join("inside/new.png"))
+        );
+        assert!(PathArgument::new()
+            .root(root.join("missing"))
+            .parse("new.png")
+            .is_err());
+        std::fs::remove_dir_all(&outside).unwrap();
+    }
+
+    std::fs::remove_dir_all(&root).unwrap();
+}
+
+#[test]
+fn network_arguments() {
+    /// Errors of network arguments cover the whole argument.
+    fn malformed<T>(token: &str, message: &str) -> crate::Result<T> {
+        Err(Error::InvalidCommand(InvalidCommandReason::SyntaxError {
+            message: message.to_string(),
+            span: Span::new(0, token.len()),
+        }))
+    }
+
+    assert_eq!(
+        IpAddrArgument.parse("10.0.0.1"),
+        Ok(IpAddr::from([10, 0, 0, 1]))
+    );
+    assert_eq!(
+        IpAddrArgument.parse("::1"),
+        Ok(IpAddr::from(Ipv6Addr::LOCALHOST))
+    );
+    assert_eq!(
+        IpAddrArgument.parse("10.0.0.256"),
+        malformed("10.0.0.256", "`10.0.0.256` is not a valid IPv4 address")
+    );
+    assert_eq!(
+        IpAddrArgument.parse("::g"),
+        malformed("::g", "`::g` is not a valid IPv6 address")
+    );
+
+    assert_eq!(
+        SocketAddrArgument.parse("127.0.0.1:25565"),
+        Ok(SocketAddr::from(([127, 0, 0, 1], 25565)))
+    );
+    assert_eq!(
+        SocketAddrArgument.parse("[::1]:80"),
+        Ok(SocketAddr::from((Ipv6Addr::LOCALHOST, 80)))
+    );
+    assert_eq!(
+        SocketAddrArgument.parse("127.0.0.1"),
+        malformed("127.0.0.1", "missing port")
+    );
+    assert_eq!(
+        SocketAddrArgument.parse("127.0.0.1:"),
+        malformed("127.0.0.1:", "missing port")
+    );
+    assert_eq!(
+        SocketAddrArgument.parse("127.0.0.1:65536"),
+        malformed(
+            "127.0.0.1:65536",
+            "`65536` is not a valid port, expected 0 to 65535"
+        )
+    );
+    assert_eq!(
+        SocketAddrArgument.parse("::1:80"),
+        malformed(
+            "::1:80",
+            "IPv6 addresses must be written in brackets, like `[::1]:8080`"
+        )
+    );
+    assert_eq!(
+        SocketAddrArgument.parse("[::1:80"),
+        malformed("[::1:80", "missing `]` after the IPv6 address")
+    );
+
+    let range = IpNetArgument.parse("10.1.2.3/8").unwrap();
+    assert_eq!(range.to_string(), "10.1.2.3/8");
+    assert_eq!(range.network(), IpAddr::from([10, 0, 0, 0]));
+    assert!(range.contains(IpAddr::from([10, 255, 0, 1])));
+    assert!(!range.contains(IpAddr::from([11, 0, 0, 1])));
+    assert!(!range.contains(IpAddr::from(Ipv6Addr::LOCALHOST)));
+    let range = IpNetArgument.parse("2001:db8::/32").unwrap();
+    assert!(range.contains("2001:db8:ffff::1".parse().unwrap()));
+    assert!(!range.contains("2001:db9::1".parse().unwrap()));
+    let single = IpNetArgument.parse("192.168.0.7").unwrap();
+    assert_eq!(single.prefix(), 32);
+    assert!(!single.contains(IpAddr::from([192, 168, 0, 8])));
+    assert!(IpNetArgument
+        .parse("0.0.0.0/0")
+        .unwrap()
+        .contains(IpAddr::from([1, 2, 3, 4])));
+    assert!(IpNetArgument
+        .parse("::/128")
+        .unwrap()
+        .contains(IpAddr::from(Ipv6Addr::UNSPECIFIED)));
+    assert_eq!(
+        IpNetArgument.parse("10.0.0.0/"),
+        malformed("10.0.0.0/", "missing prefix length after `/`")
+    );
+    assert_eq!(
+        IpNetArgument.parse("10.0.0.0/x"),
+        malformed("10.0.0.0/x", "`x` is not a valid prefix length")
+    );
+    assert_eq!(
+        IpNetArgument.parse("10.0.0.0/33"),
+        malformed(
+            "10.0.0.0/33",
+            "prefix length 33 is too long, expected at most 32"
+        )
+    );
+    assert_eq!(
+        IpNetArgument.parse("/8"),
+        malformed("/8", "missing IP address")
+    );
+
+    let url = UrlArgument::new()
+        .parse("HTTPS://example.com:8443/api/v1?key=value#top")
+        .unwrap();
+    assert_eq!(
+        url,
+        Url {
+            scheme: "https".to_string(),
+            host: "example.com".to_string(),
+            port: Some(8443),
+            path: "/api/v1".to_string(),
+            query: Some("key=value".to_string()),
+            fragment: Some("top".to_string()),
+        }
+    );
+    assert_eq!(
+        url.to_string(),
+        "https://example.com:8443/api/v1?key=value#top"
+    );
+    assert_eq!(
+        UrlArgument::new().parse("http://[::1]").map(|url| url.host),
+        Ok("[::1]".to_string())
+    );
+    assert_eq!(
+        UrlArgument::new().parse("example.com"),
+        malformed("example.com", "missing scheme, like `https://`")
+    );
+    assert_eq!(
+        UrlArgument::new().parse("ftp://example.com"),
+        malformed(
+            "ftp://example.com",
+            "scheme `ftp` is not allowed, expected one of http, https"
+        )
+    );
+    assert!(UrlArgument::new()
+        .schemes(["ftp"])
+        .parse("ftp://example.com")
+        .is_ok());
+    assert_eq!(
+        UrlArgument::new().parse("https:///path"),
+        malformed("https:///path", "missing host")
+    );
+    assert_eq!(
+        UrlArgument::new().parse("https://exa mple.com"),
+        malformed("https://exa mple.com", "`exa mple.com` is not a valid host")
+    );
+    assert_eq!(
+        UrlArgument::new().parse("https://user@example.com"),
+        malformed(
+            "https://user@example.com",
+            "user info is not allowed in the URL"
+        )
+    );
+    assert_eq!(
+        UrlArgument::new().parse("https://example.com:http"),
+        malformed(
+            "https://example.com:http",
+            "`http` is not a valid port, expected 0 to 65535"
+        )
+    );
+    assert_eq!(
+        UrlArgument::new().suggest("ht"),
+        vec!["http://", "https://"]
+    );
+}
+
+#[cfg(feature = "json")]
+#[test]
+fn json_argument() {
+    use crate::argument::json::JsonArgument;
+    use serde_json::{json, Value};
+    use std::collections::HashMap;
+
+    let dispatcher = Dispatcher::builder()
+        .dialect(Dialect::extended())
+        .base_context(())
+        .context_factory(|_| ())
+        .child(
+            Command::literal("spawn").child(
+                Command::argument("entity", StringArgument, true).child(
+                    Command::argument("data", JsonArgument::<Value>::new(), true)
+                        .exec(|ctx| ctx.get("data", JsonArgument::<Value>::new())),
+                ),
+            ),
+        )
+        .child(Command::literal("stats").child(
+            Command::argument("stats", JsonArgument::<HashMap<String, u32>>::new(), true).exec(
+                |ctx| {
+                    let stats = ctx.get("stats", JsonArgument::<HashMap<String, u32>>::new())?;
+                    Ok(json!(stats.values().sum::<u32>()))
+                },
+            ),
+        ))
+        .child(
+            Command::literal("say").child(
+                Command::argument("message", StringArgument, true)
+                    .exec(|ctx| Ok(json!(ctx.get("message", StringArgument)?))),
+            ),
+        )
+        .build()
+        .unwrap();
+
+    assert_eq!(
+        dispatcher.run_command(r#"spawn zombie {"health": 40, "tags": ["boss", "big one"]}"#),
+        Ok(vec![json!({"health": 40, "tags": ["boss", "big one"]})])
+    );
+    assert_eq!(
+        dispatcher.run_command(r#"spawn a [1, {"b": "]"}]; spawn b 2"#),
+        Ok(vec![json!([1, {"b": "]"}]), json!(2)])
+    );
+    assert_eq!(
+        dispatcher.run_command(r#"spawn a '{"text": "a; b"}'"#),
+        Ok(vec![json!({"text": "a; b"})])
+    );
+    assert_eq!(
+        dispatcher.run_command(r#"spawn a "a \" b"; spawn b "c""#),
+        Ok(vec![json!("a \" b"), json!("c")])
+    );
+    assert_eq!(
+        dispatcher.run_command(r#"spawn a {"text":"x;y"}"#),
+        Ok(vec![json!({"text": "x;y"})])
+    );
+    assert_eq!(
+        dispatcher.run_command(r#"spawn a {"text": ["x; y", "];"]}; spawn b 2"#),
+        Ok(vec![json!({"text": ["x; y", "];"]}), json!(2)])
+    );
+    // only JSON arguments read past `;`, and only when it is inside their value
+    assert_eq!(
+        dispatcher.run_command("say [oops; say {a; say b"),
+        Ok(vec![json!("[oops"), json!("{a"), json!("b")])
+    );
+    assert_eq!(
+        dispatcher.run_command(r#"stats {"str": 2, "dex": 3}"#),
+        Ok(vec![json!(5)])
+    );
+    assert_eq!(
+        dispatcher.run_command(r#"spawn a {"health": 40"#),
+        Err(Error::InvalidCommand(InvalidCommandReason::UnknownCommand))
+    );
+    assert_eq!(
+        dispatcher.run_command(r#"spawn a {"health": 40} extra"#),
+        Err(Error::InvalidCommand(InvalidCommandReason::UnknownCommand))
+    );
+
+    let syntax_error = |message: &str, start, end| {
+        Err(Error::InvalidCommand(InvalidCommandReason::SyntaxError {
+            message: message.to_string(),
+            span: Span::new(start, end),
+        }))
+    };
+    assert_eq!(
+        dispatcher.run_command(r#"spawn a {"health": 4O}"#),
+        syntax_error("expected `,` or `}`", 20, 21)
+    );
+    assert_eq!(
+        dispatcher.run_command(r#"stats {"str": "lots"}"#),
+        syntax_error("invalid type: string \"lots\", expected u32", 19, 20)
+    );
+    assert_eq!(
+        dispatcher.run_command(r#"spawn a '{"health": }'"#),
+        syntax_error("expected value", 8, 22)
+    );
+    assert_eq!(
+        dispatcher.run_command(r#"spawn a "a\q""#),
+        syntax_error("invalid escape", 11, 12)
+    );
+}
+
+#[test]
+fn snbt_argument() {
+    fn syntax_error<T>(message: &str, start: usize, end: usize) -> crate::Result<T> {
+        Err(Error::InvalidCommand(InvalidCommandReason::SyntaxError {
+            message: message.to_string(),
+            span: Span::new(start, end),
+        }))
+    }
+
+    use std::collections::BTreeMap;
+
+    let compound = |entries: Vec<(&str, Nbt)>| {
+        Nbt::Compound(
+            entries
+                .into_iter()
+                .map(|(key, value)| (key.to_string(), value))
+                .collect::<BTreeMap<_, _>>(),
+        )
+    };
+    assert_eq!(
+        SnbtArgument.parse(r#"{Count:1b,id:"minecraft:stone",tag:{Damage:0s}}"#),
+        Ok(compound(vec![
+            ("Count", Nbt::Byte(1)),
+            ("id", Nbt::String("minecraft:stone".to_string())),
+            ("tag", compound(vec![("Damage", Nbt::Short(0))])),
+        ]))
+    );
+    assert_eq!(
+        SnbtArgument.parse(r#"[ 1.5f, 2F ]"#),
+        Ok(Nbt::List(vec![Nbt::Float(1.5), Nbt::Float(2.0)]))
+    );
+    assert_eq!(
+        SnbtArgument.parse(
+            r#"{a: [B; 1b, -2B], b: [I;], c: [L; 3l], d: 1e3, e: 2d, f: true, g: 'it''s', h: stone}"#
+        ),
+        Err(Error::InvalidCommand(InvalidCommandReason::SyntaxError {
+            message: "expected `,` or `}`".to_string(),
+            span: Span::new(70, 71),
+        }))
+    );
+    assert_eq!(
+        SnbtArgument.parse(
+            r#"{a: [B; 1b, -2B], b: [I;], c: [L; 3l], d: 1e3, e: 2d, f: true, g: 'it\'s', "h i": stone}"#
+        ),
+        Ok(compound(vec![
+            ("a", Nbt::ByteArray(vec![1, -2])),
+            ("b", Nbt::IntArray(vec![])),
+            ("c", Nbt::LongArray(vec![3])),
+            ("d", Nbt::Double(1000.0)),
+            ("e", Nbt::Double(2.0)),
+            ("f", Nbt::Byte(1)),
+            ("g", Nbt::String("it's".to_string())),
+            ("h i", Nbt::String("stone".to_string())),
+        ]))
+    );
+    assert_eq!(SnbtArgument.parse("3x"), Ok(Nbt::String("3x".to_string())));
+    assert_eq!(SnbtArgument.parse("-7"), Ok(Nbt::Int(-7)));
+    assert_eq!(SnbtArgument.parse("7L"), Ok(Nbt::Long(7)));
+
+    assert_eq!(
+        SnbtArgument.parse("{a:1,}"),
+        syntax_error("expected a key", 5, 6)
+    );
+    assert_eq!(
+        SnbtArgument.parse("{a 1}"),
+        syntax_error("expected `:`", 3, 4)
+    );
+    assert_eq!(
+        SnbtArgument.parse("{a:300b}"),
+        syntax_error("`300b` is out of range for a byte", 3, 7)
+    );
+    assert_eq!(
+        SnbtArgument.parse("3000000000"),
+        syntax_error(
+            "`3000000000` is out of range for an int, add `L` for a long",
+            0,
+            10
+        )
+    );
+    assert_eq!(
+        SnbtArgument.parse("[1, 2b]"),
+        syntax_error("expected int in the list, found byte", 4, 6)
+    );
+    assert_eq!(
+        SnbtArgument.parse("[I; 1, 2L]"),
+        syntax_error("expected int in the array, found long", 7, 9)
+    );
+    assert_eq!(
+        SnbtArgument.parse(r#"{a:"x"#),
+        syntax_error("unclosed string", 3, 5)
+    );
+    assert_eq!(
+        SnbtArgument.parse(r#"{a:"\n"}"#),
+        syntax_error("invalid escape `\\n`", 4, 6)
+    );
+    assert_eq!(
+        SnbtArgument.parse("{a:1} x"),
+        syntax_error("unexpected `x` after the value", 6, 7)
+    );
+    assert_eq!(
+        SnbtArgument.parse("[1,"),
+        syntax_error("expected a value", 3, 3)
+    );
+    for double in ["1e999", "-1e999d", "1e999D"] {
+        assert_eq!(
+            SnbtArgument.parse(double),
+            syntax_error(
+                &format!("`{double}` is out of range for a double"),
+                0,
+                double.len()
+            )
+        );
+    }
+    let nested = |depth: usize| "[".repeat(depth) + &"]".repeat(depth);
+    assert!(SnbtArgument.parse(&nested(512)).is_ok());
+    assert_eq!(
+        SnbtArgument.parse(&nested(513)),
+        syntax_error("too deeply nested, expected at most 512 levels", 512, 513)
+    );
+    assert_eq!(
+        SnbtArgument.parse(&"{a:".repeat(600)),
+        syntax_error("too deeply nested, expected at most 512 levels", 1536, 1537)
+    );
+
+    let dispatcher = Dispatcher::builder()
+        .base_context(())
+        .context_factory(|_| ())
+        .child(
+            Command::literal("give").child(
+                Command::argument("item", StringArgument, true).child(
+                    Command::argument("nbt", SnbtArgument, true)
+                        .exec(|ctx| ctx.get("nbt", SnbtArgument)),
+                ),
+            ),
+        )
+        .build()
+        .unwrap();
+    assert_eq!(
+        dispatcher
+            .run_command(r#"give stone {display: {Name: "Big Bob"}, Count: 64b}; give dirt 1"#),
+        Ok(vec![
+            compound(vec![
+                (
+                    "display",
+                    compound(vec![("Name", Nbt::String("Big Bob".to_string()))])
+                ),
+                ("Count", Nbt::Byte(64)),
+            ]),
+            Nbt::Int(1),
+        ])
+    );
+    assert_eq!(
+        dispatcher.run_command("give stone {Count: 64b, }"),
+        syntax_error("expected a key", 24, 25)
+    );
+    assert_eq!(
+        dispatcher.run_command(r#"give stone {a:"x;y", b: 'z; w'}; give dirt [I; 1, 2]"#),
+        Ok(vec![
+            compound(vec![
+                ("a", Nbt::String("x;y".to_string())),
+                ("b", Nbt::String("z; w".to_string())),
+            ]),
+            Nbt::IntArray(vec![1, 2]),
+        ])
+    );
+    assert_eq!(
+        dispatcher.run_command(r#"give stone "a b"; give dirt 'c\'d'"#),
+        Ok(vec![
+            Nbt::String("a b".to_string()),
+            Nbt::String("c'd".to_string())
+        ])
+    );
+    assert_eq!(
+        dispatcher.run_command(&format!("give stone {}", nested(200_000))),
+        syntax_error("too deeply nested, expected at most 512 levels", 523, 524)
+    );
+}
+
+#[test]
+fn resource_id_argument() {
+    fn syntax_error<T>(message: &str, start: usize, end: usize) -> crate::Result<T> {
+        Err(Error::InvalidCommand(InvalidCommandReason::SyntaxError {
+            message: message.to_string(),
+            span: Span::new(start, end),
+        }))
+    }
+
+    let ids = ResourceIdArgument::default();
+    assert_eq!(
+        ids.parse("stone"),
+        Ok(ResourceId::new("minecraft", "stone"))
+    );
+    assert_eq!(
+        ids.parse("mymod:block/copper_ore"),
+        Ok(ResourceId::new("mymod", "block/copper_ore"))
+    );
+    assert_eq!(
+        ResourceIdArgument::new("game")
+            .parse("sounds/boom.ogg")
+            .map(|id| id.to_string()),
+        Ok("game:sounds/boom.ogg".to_string())
+    );
+    assert_eq!(
+        ids.parse("Stone"),
+        syntax_error("invalid character `S` in path", 0, 1)
+    );
+    assert_eq!(
+        ids.parse("my/mod:stone"),
+        syntax_error("invalid character `/` in namespace", 2, 3)
+    );
+    assert_eq!(
+        ids.parse("mymod:a:b"),
+        syntax_error("invalid character `:` in path", 7, 8)
+    );
+    assert_eq!(ids.parse(":stone"), syntax_error("missing namespace", 0, 0));
+    assert_eq!(ids.parse("mymod:"), syntax_error("missing path", 6, 6));
+
+    #[derive(Debug)]
+    struct Game {
+        items: Vec<ResourceId>,
+    }
+
+    let dispatcher = Dispatcher::builder()
+        .base_context(())
+        .context_factory(|_| Game {
+            items: vec![
+                ResourceId::new("minecraft", "stone"),
+                ResourceId::new("minecraft", "stick"),
+                ResourceId::new("minecraft", "dirt"),
+                ResourceId::new("mymod", "steel"),
+                ResourceId::new("mymod", "mithril"),
+            ],
+        })
+        .child(
+            Command::literal("give").child(
+                Command::argument("item", ResourceIdArgument::default(), true)
+                    .suggestions(|game: &Game, partial| {
+                        ResourceIdArgument::default().complete(&game.items, partial)
+                    })
+                    .exec(|ctx| Ok(ctx.get("item", ResourceIdArgument::default())?.to_string())),
+            ),
+        )
+        .build()
+        .unwrap();
+
+    let complete = |input: &str| dispatcher.complete(input).candidates;
+    assert_eq!(complete("give st"), vec!["stick", "stone"]);
+    assert_eq!(
+        complete("give m"),
+        vec![
+            "minecraft:dirt",
+            "minecraft:stick",
+            "minecraft:stone",
+            "mymod:mithril",
+            "mymod:steel"
+        ]
+    );
+    assert_eq!(
+        complete("give mymod:"),
+        vec!["mymod:mithril", "mymod:steel"]
+    );
+    assert_eq!(complete("give minecraft:d"), vec!["minecraft:dirt"]);
+    assert!(complete("give mymod:x").is_empty());
+    assert_eq!(
+        dispatcher.run_command("give stone"),
+        Ok(vec!["minecraft:stone".to_string()])
+    );
+}
+
+#[test]
+fn list_and_map_arguments() {
+    fn syntax_error<T>(message: &str, start: usize, end: usize) -> crate::Result<T> {
+        Err(Error::InvalidCommand(InvalidCommandReason::SyntaxError {
+            message: message.to_string(),
+            span: Span::new(start, end),
+        }))
+    }
+
+    let tags = ListArgument::new(StringArgument);
+    assert_eq!(
+        tags.parse("a,b,c"),
+        Ok(vec!["a".into(), "b".into(), "c".into()])
+    );
+    assert_eq!(
+        tags.parse(r#"a,"b, c",'d\e'"#),
+        Ok(vec!["a".into(), "b, c".into(), r"d\e".into()])
+    );
+    assert_eq!(
+        ListArgument::new(IntArgument).separator('/').parse("1/2/3"),
+        Ok(vec![1, 2, 3])
+    );
+    assert_eq!(tags.parse("a,,c"), syntax_error("missing element", 2, 2));
+    assert_eq!(
+        tags.parse(r#"a,"b"c"#),
+        syntax_error("unexpected `c`", 5, 6)
+    );
+    assert_eq!(
+        tags.parse(r#"a,"b"#),
+        syntax_error("unclosed or invalid quoted string", 2, 4)
+    );
+    assert_eq!(
+        ListArgument::new(IntArgument).parse("1,x,3"),
+        syntax_error("invalid element 2 `x`", 2, 3)
+    );
+    assert_eq!(
+        ListArgument::new(ResourceIdArgument::default()).parse("stone,Dirt"),
+        syntax_error("invalid element 2: invalid character `D` in path", 6, 7)
+    );
+    assert_eq!(tags.suggest("a,b"), Vec::<String>::new());
+    assert_eq!(
+        ListArgument::new(BoolArgument).suggest("true,f"),
+        vec!["true,false"]
+    );
+
+    let attrs = MapArgument::new(StringArgument, IntArgument);
+    assert_eq!(
+        attrs.parse(r#"hp:10,"move speed":2"#),
+        Ok(vec![("hp".into(), 10), ("move speed".into(), 2)])
+    );
+    assert_eq!(
+        MapArgument::new(StringArgument, IntArgument)
+            .separator(';')
+            .assignment('=')
+            .parse("a=1;b=2"),
+        Ok(vec![("a".into(), 1), ("b".into(), 2)])
+    );
+    assert_eq!(
+        attrs.parse("hp:10,speed"),
+        syntax_error("expected `:` after the key", 11, 11)
+    );
+    assert_eq!(
+        attrs.parse("hp:10,speed:fast"),
+        syntax_error("invalid value in entry 2 `fast`", 12, 16)
+    );
+    assert_eq!(
+        MapArgument::new(BoolArgument, StringArgument).suggest("true:x,f"),
+        vec!["true:x,false"]
+    );
+    assert_eq!(
+        MapArgument::new(StringArgument, BoolArgument).suggest("a:1,b:t"),
+        vec!["a:1,b:true"]
+    );
+
+    let dispatcher = Dispatcher::builder()
+        .base_context(())
+        .context_factory(|_| ())
+        .child(Command::literal("tag").child(
+            Command::argument("tags", ListArgument::new(StringArgument), true).child(
+                Command::argument("target", StringArgument, true).exec(|ctx| {
+                    let mut tags = ctx.get("tags", ListArgument::new(StringArgument))?;
+                    tags.push(ctx.get("target", StringArgument)?);
+                    Ok(tags)
+                }),
+            ),
+        ))
+        .child(Command::literal("attrs").child(
+            Command::argument("attrs", MapArgument::new(StringArgument, IntArgument), true).exec(
+                |ctx| {
+                    let attrs = ctx.get("attrs", MapArgument::new(StringArgument, IntArgument))?;
+                    Ok(attrs
+                        .into_iter()
+                        .map(|(key, value)| format!("{key}={value}"))
+                        .collect())
+                },
+            ),
+        ))
+        .build()
+        .unwrap();
+
+    assert_eq!(
+        dispatcher.run_command("tag tags=a,b,c bob"),
+        Ok(vec![vec![
+            "a".to_string(),
+            "b".into(),
+            "c".into(),
+            "bob".into()
+        ]])
+    );
+    assert_eq!(
+        dispatcher.run_command(r#"tag "b c",d bob"#),
+        Ok(vec![vec!["b c".to_string(), "d".into(), "bob".into()]])
+    );
+    assert_eq!(
+        dispatcher.run_command(r#"tag a,"b c",d bob"#),
+        Ok(vec![vec![
+            "a".to_string(),
+            "b c".into(),
+            "d".into(),
+            "bob".into()
+        ]])
+    );
+    assert_eq!(
+        dispatcher.run_command(r#"tag tags="a b" bob"#),
+        Ok(vec![vec!["a b".to_string(), "bob".into()]])
+    );
+    assert_eq!(
+        dispatcher.run_command("attrs hp:10,speed:2"),
+        Ok(vec![vec!["hp=10".to_string(), "speed=2".into()]])
+    );
+    assert_eq!(
+        dispatcher.run_command(r#"attrs hp:10,"max speed":2"#),
+        Ok(vec![vec!["hp=10".to_string(), "max speed=2".into()]])
+    );
+    assert_eq!(
+        dispatcher.run_command(r#"attrs "max hp:10,speed:2""#),
+        Ok(vec![vec!["max hp=10".to_string(), "speed=2".into()]])
+    );
+    assert_eq!(
+        dispatcher.run_command(r#"attrs "max hp":10,speed:2"#),
+        Ok(vec![vec!["max hp=10".to_string(), "speed=2".into()]])
+    );
+    // named arguments may come last
+    assert_eq!(
+        dispatcher.run_command("attrs attrs=hp:10"),
+        Ok(vec![vec!["hp=10".to_string()]])
+    );
+    assert_eq!(
+        dispatcher.run_command("tag bob tags=a,b"),
+        Ok(vec![vec!["a".to_string(), "b".into(), "bob".into()]])
+    );
+    // the target is still missing
+    assert_eq!(
+        dispatcher.run_command("tag tags=a,b,c"),
+        Err(Error::InvalidCommand(InvalidCommandReason::UnknownCommand))
+    );
+    assert_eq!(
+        dispatcher.run_command(r#"tag r"a b",c bob"#),
+        Ok(vec![vec!["a b".to_string(), "c".into(), "bob".into()]])
+    );
+    assert_eq!(
+        dispatcher.run_command("attrs hp:10,speed:x"),
+        syntax_error("invalid value in entry 2 `x`", 18, 19)
+    );
+    assert_eq!(
+        dispatcher.run_command("attrs hp:10,speed"),
+        Err(Error::InvalidCommand(InvalidCommandReason::UnknownCommand))
+    );
+}